@@ -6,13 +6,41 @@ use std::num::NonZeroU32;
 
 use devotee_backend::winit::dpi::PhysicalPosition;
 use devotee_backend::winit::window::Window;
-use devotee_backend::{Backend, BackendImage, Converter};
+use devotee_backend::{Backend, BackendImage, Converter, Frame, Placement, Scaling};
 use pixels::wgpu::{Color, TextureFormat};
 use pixels::{Pixels, PixelsBuilder, SurfaceTexture};
 
 /// [Pixels](https://crates.io/crates/pixels)-based backend.
+///
+/// Integer scaling is done on the GPU with the pixel buffer of the image resolution.
+/// Other scaling modes and post-processing scale the image on the CPU to the window resolution.
 pub struct PixelsBackend {
     pixels: Pixels,
+    buffer: Vec<u32>,
+    buffer_size: (u32, u32),
+    is_native: bool,
+}
+
+impl PixelsBackend {
+    fn resize_buffer(&mut self, size: (u32, u32)) -> Option<()> {
+        if self.buffer_size != size {
+            self.pixels.resize_buffer(size.0, size.1).ok()?;
+            self.buffer_size = size;
+        }
+        Some(())
+    }
+
+    fn write_frame<I: Iterator<Item = u32>>(&mut self, colors: I) {
+        for (chunk, argb) in self.pixels.frame_mut().chunks_exact_mut(4).zip(colors) {
+            let r = ((argb & 0x00ff0000) >> 16) as u8;
+            let g = ((argb & 0x0000ff00) >> 8) as u8;
+            let b = (argb & 0x000000ff) as u8;
+            chunk[0] = r;
+            chunk[1] = g;
+            chunk[2] = b;
+            chunk[3] = 0xff;
+        }
+    }
 }
 
 impl Backend for PixelsBackend {
@@ -36,7 +64,12 @@ impl Backend for PixelsBackend {
         }
         .ok()?;
 
-        Some(PixelsBackend { pixels })
+        Some(PixelsBackend {
+            pixels,
+            buffer: Vec::new(),
+            buffer_size: resolution,
+            is_native: true,
+        })
     }
 
     fn resize(&mut self, width: NonZeroU32, height: NonZeroU32) -> Option<()> {
@@ -47,8 +80,10 @@ impl Backend for PixelsBackend {
         &mut self,
         image: &'a dyn BackendImage<'a, P, Iterator = I>,
         converter: &dyn Converter<Palette = P>,
-        _window: &Window,
+        window: &Window,
         background: u32,
+        scaling: Scaling,
+        post_process: Option<&mut dyn FnMut(&mut Frame)>,
     ) -> Option<()>
     where
         I: Iterator<Item = &'a P>,
@@ -64,20 +99,34 @@ impl Backend for PixelsBackend {
             a: 1.0,
         });
 
-        for (chunk, pixel) in self
-            .pixels
-            .frame_mut()
-            .chunks_exact_mut(4)
-            .zip(image.pixels())
-        {
-            let argb = converter.convert(pixel);
-            let r = ((argb & 0x00ff0000) >> 16) as u8;
-            let g = ((argb & 0x0000ff00) >> 8) as u8;
-            let b = (argb & 0x000000ff) as u8;
-            chunk[0] = r;
-            chunk[1] = g;
-            chunk[2] = b;
-            chunk[3] = 0xff;
+        let size = window.inner_size();
+        let resolution = (image.width(), image.height());
+        let Some(placement) = Placement::new(scaling, (size.width, size.height), resolution) else {
+            return Some(());
+        };
+
+        self.is_native = scaling == Scaling::Integer
+            && post_process.is_none()
+            && size.width >= resolution.0
+            && size.height >= resolution.1;
+
+        if self.is_native {
+            self.resize_buffer(resolution)?;
+            self.write_frame(image.pixels().map(|pixel| converter.convert(pixel)));
+        } else {
+            self.resize_buffer((size.width, size.height))?;
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.resize((size.width * size.height) as usize, 0);
+            buffer.fill(background);
+            placement.render(image, converter, &mut buffer, size.width);
+
+            if let Some(post_process) = post_process {
+                let mut frame = Frame::new(&mut buffer, size.width, size.height, placement);
+                post_process(&mut frame);
+            }
+
+            self.write_frame(buffer.iter().copied());
+            self.buffer = buffer;
         }
 
         self.pixels.render().ok()
@@ -86,12 +135,20 @@ impl Backend for PixelsBackend {
     fn window_pos_to_inner(
         &self,
         position: PhysicalPosition<f64>,
-        _window: &Window,
-        _resolution: (u32, u32),
+        window: &Window,
+        resolution: (u32, u32),
+        scaling: Scaling,
     ) -> Result<(i32, i32), (i32, i32)> {
-        self.pixels
-            .window_pos_to_pixel((position.x as f32, position.y as f32))
-            .map(|(a, b)| (a as i32, b as i32))
-            .map_err(|(a, b)| (a as i32, b as i32))
+        if self.is_native {
+            self.pixels
+                .window_pos_to_pixel((position.x as f32, position.y as f32))
+                .map(|(a, b)| (a as i32, b as i32))
+                .map_err(|(a, b)| (a as i32, b as i32))
+        } else {
+            let size = window.inner_size();
+            Placement::new(scaling, (size.width, size.height), resolution)
+                .ok_or((0, 0))?
+                .window_pos_to_inner((position.x, position.y))
+        }
     }
 }
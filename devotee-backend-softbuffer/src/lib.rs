@@ -6,7 +6,7 @@ use std::num::NonZeroU32;
 
 use devotee_backend::winit::dpi::PhysicalPosition;
 use devotee_backend::winit::window::Window;
-use devotee_backend::{Backend, BackendImage, Converter};
+use devotee_backend::{Backend, BackendImage, Converter, Frame, Placement, Scaling};
 use softbuffer::{Context, Surface};
 
 /// [Softbuffer](https://crates.io/crates/softbuffer)-based backend.
@@ -43,6 +43,8 @@ impl Backend for SoftbufferBackend {
         converter: &dyn Converter<Palette = P>,
         window: &Window,
         background: u32,
+        scaling: Scaling,
+        post_process: Option<&mut dyn FnMut(&mut Frame)>,
     ) -> Option<()>
     where
         I: Iterator<Item = &'a P>,
@@ -57,30 +59,21 @@ impl Backend for SoftbufferBackend {
 
         buffer.fill(background);
 
-        let scale_x = surface_size.width / image.width();
-        let scale_y = surface_size.height / image.height();
-
-        let minimal_scale = scale_x.min(scale_y);
-
-        if minimal_scale < 1 {
-        } else {
-            let start_x = (surface_size.width - image.width() * minimal_scale) as usize / 2;
-            let start_y = (surface_size.height - image.height() * minimal_scale) as usize / 2;
-
-            for y in 0..image.height() {
-                for x in 0..image.width() {
-                    // Safety: we are sure that we are in a proper range due to for loops proper arguments.
-                    let pixel = unsafe { image.pixel_unsafe(x, y) };
-                    let rgb = converter.convert(pixel);
-
-                    for iy in 0..minimal_scale {
-                        let index = (start_x + (x * minimal_scale) as usize)
-                            + (iy as usize + start_y + (y * minimal_scale) as usize)
-                                * surface_size.width as usize;
-
-                        buffer[index..index + minimal_scale as usize].fill(rgb);
-                    }
-                }
+        if let Some(placement) = Placement::new(
+            scaling,
+            (surface_size.width, surface_size.height),
+            (image.width(), image.height()),
+        ) {
+            placement.render(image, converter, &mut buffer, surface_size.width);
+
+            if let Some(post_process) = post_process {
+                let mut frame = Frame::new(
+                    &mut buffer,
+                    surface_size.width,
+                    surface_size.height,
+                    placement,
+                );
+                post_process(&mut frame);
             }
         }
 
@@ -92,34 +85,11 @@ impl Backend for SoftbufferBackend {
         position: PhysicalPosition<f64>,
         window: &Window,
         resolution: (u32, u32),
+        scaling: Scaling,
     ) -> Result<(i32, i32), (i32, i32)> {
         let size = window.inner_size();
-        let scale_x = size.width / resolution.0;
-        let scale_y = size.height / resolution.1;
-
-        let minimal_scale = scale_x.min(scale_y);
-
-        if minimal_scale < 1 {
-            Err((0, 0))
-        } else {
-            let position = (position.x as i32, position.y as i32);
-            let start_x = ((size.width - resolution.0 * minimal_scale) / 2) as i32;
-            let start_y = ((size.height - resolution.1 * minimal_scale) / 2) as i32;
-
-            let position = (
-                (position.0 - start_x) / minimal_scale as i32,
-                (position.1 - start_y) / minimal_scale as i32,
-            );
-
-            if position.0 < 0
-                || position.0 >= resolution.0 as i32
-                || position.1 < 0
-                || position.1 >= resolution.1 as i32
-            {
-                Err(position)
-            } else {
-                Ok(position)
-            }
-        }
+        Placement::new(scaling, (size.width, size.height), resolution)
+            .ok_or((0, 0))?
+            .window_pos_to_inner((position.x, position.y))
     }
 }
@@ -24,12 +24,15 @@ pub trait Backend: Sized {
     fn resize(&mut self, width: NonZeroU32, height: NonZeroU32) -> Option<()>;
 
     /// Draw image on the backend.
+    /// The `post_process` is applied to the output frame right before presenting it.
     fn draw_image<'a, P: 'a, I>(
         &mut self,
         image: &'a dyn BackendImage<'a, P, Iterator = I>,
         converter: &dyn Converter<Palette = P>,
         window: &Window,
         background: u32,
+        scaling: Scaling,
+        post_process: Option<&mut dyn FnMut(&mut Frame)>,
     ) -> Option<()>
     where
         I: Iterator<Item = &'a P>;
@@ -40,9 +43,220 @@ pub trait Backend: Sized {
         position: PhysicalPosition<f64>,
         window: &Window,
         resolution: (u32, u32),
+        scaling: Scaling,
     ) -> Result<(i32, i32), (i32, i32)>;
 }
 
+/// Scaling of the image to the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scaling {
+    /// Integer scale with letterboxing.
+    /// Falls back to `Fit` when the window is smaller than the image.
+    #[default]
+    Integer,
+    /// Fractional scale preserving aspect ratio with letterboxing.
+    Fit,
+    /// Stretch to the whole window ignoring aspect ratio.
+    Stretch,
+    /// Fractional scale preserving aspect ratio, cropping the image to fill the window.
+    Fill,
+}
+
+/// Placement of the image in the window, in window pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    resolution: (u32, u32),
+}
+
+impl Placement {
+    /// Calculate placement of the image with `resolution` in the window of `window` size.
+    /// Returns `None` if any of the sizes is zero.
+    pub fn new(scaling: Scaling, window: (u32, u32), resolution: (u32, u32)) -> Option<Self> {
+        if window.0 == 0 || window.1 == 0 || resolution.0 == 0 || resolution.1 == 0 {
+            return None;
+        }
+        let fractional = |factor: f64| {
+            (
+                ((resolution.0 as f64 * factor).round() as u32).max(1),
+                ((resolution.1 as f64 * factor).round() as u32).max(1),
+            )
+        };
+        let factor_x = window.0 as f64 / resolution.0 as f64;
+        let factor_y = window.1 as f64 / resolution.1 as f64;
+        let (width, height) = match scaling {
+            Scaling::Integer => {
+                let scale = (window.0 / resolution.0).min(window.1 / resolution.1);
+                if scale < 1 {
+                    fractional(factor_x.min(factor_y))
+                } else {
+                    (resolution.0 * scale, resolution.1 * scale)
+                }
+            }
+            Scaling::Fit => fractional(factor_x.min(factor_y)),
+            Scaling::Stretch => window,
+            Scaling::Fill => fractional(factor_x.max(factor_y)),
+        };
+        let x = (window.0 as i32 - width as i32) / 2;
+        let y = (window.1 as i32 - height as i32) / 2;
+        Some(Self {
+            x,
+            y,
+            width,
+            height,
+            resolution,
+        })
+    }
+
+    /// Get position of the image top left corner.
+    pub fn origin(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    /// Get size of the image in window pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Get image size in image pixels.
+    pub fn resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    /// Get image column covering the center of the window column `x`.
+    pub fn column(&self, x: i32) -> i32 {
+        ((2 * (x - self.x) as i64 + 1) * self.resolution.0 as i64).div_euclid(2 * self.width as i64)
+            as i32
+    }
+
+    /// Get image row covering the center of the window row `y`.
+    pub fn row(&self, y: i32) -> i32 {
+        ((2 * (y - self.y) as i64 + 1) * self.resolution.1 as i64)
+            .div_euclid(2 * self.height as i64) as i32
+    }
+
+    /// Recalculate window position into image position.
+    /// Returns `Err` with the position if it is out of image bounds.
+    pub fn window_pos_to_inner(&self, position: (f64, f64)) -> Result<(i32, i32), (i32, i32)> {
+        let x = ((position.0 - self.x as f64) * self.resolution.0 as f64 / self.width as f64)
+            .floor() as i32;
+        let y = ((position.1 - self.y as f64) * self.resolution.1 as f64 / self.height as f64)
+            .floor() as i32;
+        if x < 0 || x >= self.resolution.0 as i32 || y < 0 || y >= self.resolution.1 as i32 {
+            Err((x, y))
+        } else {
+            Ok((x, y))
+        }
+    }
+
+    /// Render `image` into the `buffer` of `0x00rrggbb` pixels with nearest-neighbour scaling.
+    /// The `buffer` is considered to be `width` pixels wide.
+    pub fn render<'a, P: 'a, I>(
+        &self,
+        image: &'a dyn BackendImage<'a, P, Iterator = I>,
+        converter: &dyn Converter<Palette = P>,
+        buffer: &mut [u32],
+        width: u32,
+    ) where
+        I: Iterator<Item = &'a P>,
+    {
+        if width == 0 {
+            return;
+        }
+        let height = (buffer.len() / width as usize) as i32;
+        let from_x = self.x.max(0);
+        let to_x = (self.x + self.width as i32).min(width as i32);
+        let from_y = self.y.max(0);
+        let to_y = (self.y + self.height as i32).min(height);
+        if from_x >= to_x || from_y >= to_y {
+            return;
+        }
+
+        let columns: Vec<u32> = (from_x..to_x)
+            .map(|x| self.column(x).clamp(0, self.resolution.0 as i32 - 1) as u32)
+            .collect();
+        let mut converted = vec![0; self.resolution.0 as usize];
+        let mut last_row = None;
+        for y in from_y..to_y {
+            let row = self.row(y).clamp(0, self.resolution.1 as i32 - 1) as u32;
+            if last_row != Some(row) {
+                for (x, value) in converted.iter_mut().enumerate() {
+                    // SAFETY: `x` and `row` are clamped to the image bounds.
+                    *value = converter.convert(unsafe { image.pixel_unsafe(x as u32, row) });
+                }
+                last_row = Some(row);
+            }
+            let start = (from_x + y * width as i32) as usize;
+            let line = &mut buffer[start..start + columns.len()];
+            for (pixel, column) in line.iter_mut().zip(&columns) {
+                *pixel = converted[*column as usize];
+            }
+        }
+    }
+}
+
+/// Output frame of `0x00rrggbb` pixels, row by row.
+pub struct Frame<'a> {
+    pixels: &'a mut [u32],
+    width: u32,
+    height: u32,
+    placement: Placement,
+}
+
+impl<'a> Frame<'a> {
+    /// Create new frame over `pixels` of `width` by `height` size with the image at `placement`.
+    pub fn new(pixels: &'a mut [u32], width: u32, height: u32, placement: Placement) -> Self {
+        Self {
+            pixels,
+            width,
+            height,
+            placement,
+        }
+    }
+
+    /// Get frame width in frame pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get frame height in frame pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get placement of the image in the frame.
+    pub fn placement(&self) -> Placement {
+        self.placement
+    }
+
+    /// Get image size in image pixels.
+    pub fn resolution(&self) -> (u32, u32) {
+        self.placement.resolution
+    }
+
+    /// Get all frame pixels, row by row.
+    pub fn pixels(&self) -> &[u32] {
+        self.pixels
+    }
+
+    /// Get all frame pixels mutably, row by row.
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        self.pixels
+    }
+
+    /// Get mutable reference to the frame pixel.
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut u32> {
+        if x < self.width && y < self.height {
+            self.pixels.get_mut((x + y * self.width) as usize)
+        } else {
+            None
+        }
+    }
+}
+
 /// Converter from pallette value to `u32` value.
 pub trait Converter {
     /// Palette to convert from.
@@ -77,3 +291,101 @@ pub trait BackendImage<'a, P: 'a> {
     /// The iterator is considered to provide pixels row-by-row.
     fn pixels(&'a self) -> Self::Iterator;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [Scaling; 4] = [
+        Scaling::Integer,
+        Scaling::Fit,
+        Scaling::Stretch,
+        Scaling::Fill,
+    ];
+
+    fn placement(scaling: Scaling, window: (u32, u32)) -> Placement {
+        Placement::new(scaling, window, (32, 18)).unwrap()
+    }
+
+    #[test]
+    fn places_image_in_larger_window() {
+        let integer = placement(Scaling::Integer, (100, 70));
+        assert_eq!((integer.origin(), integer.size()), ((2, 8), (96, 54)));
+        let fit = placement(Scaling::Fit, (100, 70));
+        assert_eq!((fit.origin(), fit.size()), ((0, 7), (100, 56)));
+        let stretch = placement(Scaling::Stretch, (100, 70));
+        assert_eq!((stretch.origin(), stretch.size()), ((0, 0), (100, 70)));
+        let fill = placement(Scaling::Fill, (100, 70));
+        assert_eq!((fill.origin(), fill.size()), ((-12, 0), (124, 70)));
+    }
+
+    #[test]
+    fn places_image_in_smaller_window() {
+        let integer = placement(Scaling::Integer, (16, 16));
+        assert_eq!((integer.origin(), integer.size()), ((0, 3), (16, 9)));
+        let fit = placement(Scaling::Fit, (16, 16));
+        assert_eq!((fit.origin(), fit.size()), ((0, 3), (16, 9)));
+        let stretch = placement(Scaling::Stretch, (16, 16));
+        assert_eq!((stretch.origin(), stretch.size()), ((0, 0), (16, 16)));
+        let fill = placement(Scaling::Fill, (16, 16));
+        assert_eq!((fill.origin(), fill.size()), ((-6, 0), (28, 16)));
+    }
+
+    #[test]
+    fn rejects_zero_sizes() {
+        for scaling in MODES {
+            assert!(Placement::new(scaling, (0, 10), (32, 18)).is_none());
+            assert!(Placement::new(scaling, (10, 10), (32, 0)).is_none());
+        }
+    }
+
+    #[test]
+    fn window_position_matches_rendered_pixel() {
+        for scaling in MODES {
+            for window in [(100, 70), (64, 36), (33, 50), (16, 16), (5, 3)] {
+                let placement = placement(scaling, window);
+                let (origin, size) = (placement.origin(), placement.size());
+                for y in 0..window.1 as i32 {
+                    for x in 0..window.0 as i32 {
+                        let center = (x as f64 + 0.5, y as f64 + 0.5);
+                        let inside = x >= origin.0
+                            && y >= origin.1
+                            && x < origin.0 + size.0 as i32
+                            && y < origin.1 + size.1 as i32;
+                        let expected = (placement.column(x), placement.row(y));
+                        let actual = placement.window_pos_to_inner(center);
+                        if inside {
+                            assert_eq!(actual, Ok(expected), "{:?} {:?}", scaling, window);
+                        } else {
+                            assert!(actual.is_err(), "{:?} {:?}", scaling, window);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn image_position_round_trips() {
+        for scaling in MODES {
+            let placement = placement(scaling, (100, 70));
+            let (origin, size) = (placement.origin(), placement.size());
+            let scale = (size.0 as f64 / 32.0, size.1 as f64 / 18.0);
+            for y in 0..18 {
+                for x in 0..32 {
+                    let window = (
+                        origin.0 as f64 + (x as f64 + 0.5) * scale.0,
+                        origin.1 as f64 + (y as f64 + 0.5) * scale.1,
+                    );
+                    let inner = placement.window_pos_to_inner(window);
+                    let visible = window.0 >= 0.0 && window.0 < 100.0;
+                    if visible {
+                        assert_eq!(inner, Ok((x, y)), "{:?}", scaling);
+                    }
+                }
+            }
+        }
+        let outside = placement(Scaling::Integer, (100, 70));
+        assert_eq!(outside.window_pos_to_inner((1.0, 1.0)), Err((-1, -3)));
+    }
+}
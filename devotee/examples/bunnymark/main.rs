@@ -7,6 +7,7 @@ use devotee::app::input::key_mouse::{KeyMouse, VirtualKeyCode};
 use devotee::app::root::Root;
 use devotee::app::setup;
 use devotee::util::vector::Vector;
+use devotee::visual::batch::Batch;
 use devotee::visual::color;
 use devotee::visual::prelude::*;
 use devotee::visual::sprite::Sprite;
@@ -16,6 +17,10 @@ const BUNNY_HEIGHT: usize = 16;
 const WIDTH: usize = 128;
 const HEIGHT: usize = 128;
 const ACCELERATION: f64 = 8.0;
+const PALETTE: [u32; 16] = [
+    0x00000000, 0x001d2b53, 0x007e2553, 0x00008751, 0x00ab5236, 0x005f574f, 0x00c2c3c7, 0x00fff1e8,
+    0x00ff004d, 0x00ffa300, 0x00ffec27, 0x0000e436, 0x0029adff, 0x0083769c, 0x00ff77a8, 0x00ffccaa,
+];
 
 fn main() {
     let init_config = setup::Setup::<Config>::new(Default::default(), Default::default(), |_| {
@@ -32,12 +37,12 @@ struct Config;
 
 impl config::Config for Config {
     type Root = BunnyMark;
-    type Converter = Converter;
+    type Converter = color::Palette<FourBits>;
     type Input = KeyMouse;
     type RenderTarget = Sprite<FourBits, 128, 128>;
 
     fn converter() -> Self::Converter {
-        Converter
+        color::Palette::new(PALETTE.to_vec())
     }
 
     fn background_color() -> FourBits {
@@ -45,35 +50,6 @@ impl config::Config for Config {
     }
 }
 
-pub struct Converter;
-
-impl color::Converter for Converter {
-    type Palette = FourBits;
-    #[inline]
-    fn convert(&self, color: &Self::Palette) -> u32 {
-        {
-            match color {
-                FourBits::Black => 0x00000000,
-                FourBits::DarkBlue => 0x001d2b53,
-                FourBits::Eggplant => 0x007e2553,
-                FourBits::DarkGreen => 0x00008751,
-                FourBits::Brown => 0x00ab5236,
-                FourBits::DirtyGray => 0x005f574f,
-                FourBits::Gray => 0x00c2c3c7,
-                FourBits::White => 0x00fff1e8,
-                FourBits::Red => 0x00ff004d,
-                FourBits::Orange => 0x00ffa300,
-                FourBits::Yellow => 0x00ffec27,
-                FourBits::Green => 0x0000e436,
-                FourBits::LightBlue => 0x0029adff,
-                FourBits::Purple => 0x0083769c,
-                FourBits::Pink => 0x00ff77a8,
-                FourBits::Beige => 0x00ffccaa,
-            }
-        }
-    }
-}
-
 struct BunnyMark {
     bunnies: Vec<Bunny>,
     texture: Sprite<FourBits, BUNNY_WIDTH, BUNNY_HEIGHT>,
@@ -168,13 +144,22 @@ impl Root<Config> for BunnyMark {
     fn render(&self, render: &mut Sprite<FourBits, 128, 128>) {
         let mut render = render.painter();
         render.clear(FourBits::Black);
+        let mut batch = Batch::new();
         for bunny in self.bunnies.iter() {
-            render.image(
-                (bunny.pose.x() as i32, bunny.pose.y() as i32),
+            let flip = if bunny.velocity.x() < 0.0 {
+                Flip::Horizontal
+            } else {
+                Flip::None
+            };
+            batch.push(
                 &self.texture,
-                |_, _, p, _, _, o| p.mix(o),
+                (bunny.pose.x() as i32, bunny.pose.y() as i32),
+                bunny.pose.y() as i32,
+                flip,
+                mix(),
             );
         }
+        batch.draw(&mut render);
     }
 }
 
@@ -192,7 +177,7 @@ impl Bunny {
 
     fn update(&mut self, delta: f64) {
         *self.velocity.y_mut() += ACCELERATION;
-        self.pose = self.pose + self.velocity * delta;
+        self.pose += self.velocity * delta;
         if self.pose.x() < 0.0 {
             *self.velocity.x_mut() = self.velocity.x().abs();
         }
@@ -255,6 +240,13 @@ impl From<u8> for FourBits {
     }
 }
 
+impl From<FourBits> for usize {
+    #[inline]
+    fn from(value: FourBits) -> Self {
+        value as usize
+    }
+}
+
 impl color::Color for FourBits {
     fn mix(self, other: Self) -> Self {
         match other {
@@ -90,12 +90,11 @@ impl Root<Config> for Invert {
     fn render(&self, render: &mut Canvas<Color>) {
         let mut painter = render.painter();
         painter.clear(Color([0, 0, 0]));
-        drop(painter);
         for x in 8..(render.width() - 8) {
             for y in 8..(render.height() - 8) {
                 // SAFETY: we are iterating in safe range.
                 unsafe {
-                    *Image::unsafe_pixel_mut(render, (x, y).into()) =
+                    *ImageMut::unsafe_pixel_mut(render, (x, y).into()) =
                         Color([2 * x as u8, 2 * y as u8, 0x00]);
                 }
             }
@@ -42,11 +42,21 @@ impl config::Config for Config {
     fn background_color() -> FourBits {
         0.into()
     }
+
+    fn resize_render_target(render_target: &mut Canvas<FourBits>, resolution: Vector<u32>) -> bool {
+        render_target.resize(
+            resolution.x() as usize,
+            resolution.y() as usize,
+            FourBits::Black,
+        );
+        true
+    }
 }
 
 struct Mandelbrot {
     scale: f64,
     center: Vector<f64>,
+    expand: bool,
 }
 
 impl Default for Mandelbrot {
@@ -54,6 +64,7 @@ impl Default for Mandelbrot {
         Self {
             scale: 0.5,
             center: Vector::new(0.0, 0.0),
+            expand: false,
         }
     }
 }
@@ -83,6 +94,18 @@ impl Root<Config> for Mandelbrot {
             *self.center.y_mut() -= delta / scale;
         }
 
+        if update.input().just_pressed(Button::Expand) {
+            self.expand = !self.expand;
+            let expand = self.expand;
+            update.add_window_command(move |window| {
+                if expand {
+                    window.set_expand(Some(4));
+                } else {
+                    window.set_resolution(Vector::new(128, 128));
+                }
+            });
+        }
+
         if update.input().just_pressed(Button::Quit) {
             update.shutdown();
         }
@@ -211,6 +234,7 @@ enum Button {
     Down,
     In,
     Out,
+    Expand,
 }
 
 impl TryFrom<Option<event::VirtualKeyCode>> for Button {
@@ -225,6 +249,7 @@ impl TryFrom<Option<event::VirtualKeyCode>> for Button {
             event::VirtualKeyCode::Down => Ok(Button::Down),
             event::VirtualKeyCode::Z => Ok(Button::In),
             event::VirtualKeyCode::X => Ok(Button::Out),
+            event::VirtualKeyCode::E => Ok(Button::Expand),
             _ => Err(()),
         }
     }
@@ -72,6 +72,21 @@ impl Root<Config> for Paint {
             }
         }
 
+        if update.input().mouse().just_pressed(MouseButton::Right) {
+            if let Some(position) = self.cursor {
+                if let Some(target) = self.canvas.pixel(position).copied() {
+                    self.canvas.painter().flood_fill(
+                        position,
+                        Connectivity::Four,
+                        |pixel| *pixel == target,
+                        |_, _, pixel| Palette {
+                            value: pixel.value + 0.3,
+                        },
+                    );
+                }
+            }
+        }
+
         let delta = update.delta().as_secs_f64();
 
         update.converter_mut().hue += delta;
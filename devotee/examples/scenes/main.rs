@@ -0,0 +1,130 @@
+use devotee::app;
+use devotee::app::config;
+use devotee::app::context::Context;
+use devotee::app::input::key_mouse::{KeyMouse, VirtualKeyCode};
+use devotee::app::scene::transition::{Effect, Transition};
+use devotee::app::scene::{Scene, SceneStack};
+use devotee::app::setup;
+use devotee::visual::canvas::Canvas;
+use devotee::visual::color;
+use devotee::visual::prelude::*;
+
+fn main() {
+    let init_config = setup::Setup::<Config>::new(
+        Canvas::with_resolution(Default::default(), 128, 128),
+        Default::default(),
+        |context| {
+            context.push_scene(Menu);
+            SceneStack::new()
+        },
+    )
+    .with_title("scenes")
+    .with_scale(2);
+    let app = app::App::with_setup(init_config).unwrap();
+
+    app.run();
+}
+
+struct Config;
+
+impl config::Config for Config {
+    type Root = SceneStack<Config>;
+    type Converter = Converter;
+    type Input = KeyMouse;
+    type RenderTarget = Canvas<Color>;
+
+    fn converter() -> Self::Converter {
+        Converter
+    }
+
+    fn background_color() -> Color {
+        Color([0x00, 0x00, 0x00])
+    }
+}
+
+struct Menu;
+
+impl Scene<Config> for Menu {
+    fn update(&mut self, context: &mut Context<Config>) {
+        if context.input().keys().just_pressed(VirtualKeyCode::Escape) {
+            context.pop_scene();
+        }
+        if context.input().keys().just_pressed(VirtualKeyCode::Return) {
+            context.push_scene_with(
+                Level { position: 0 },
+                Transition::new(Effect::CircleWipe, 30),
+            );
+        }
+    }
+
+    fn render(&self, render: &mut Canvas<Color>) {
+        let mut render = render.painter();
+        render.clear(Color([0x00, 0x00, 0x80]));
+        render.rect((32, 48), (96, 80), paint(Color([0xff, 0xff, 0xff])));
+        render.triangle_f(
+            [(56, 56), (56, 72), (72, 64)],
+            paint(Color([0xff, 0xff, 0xff])),
+        );
+    }
+}
+
+struct Level {
+    position: i32,
+}
+
+impl Scene<Config> for Level {
+    fn update(&mut self, context: &mut Context<Config>) {
+        if context.input().keys().just_pressed(VirtualKeyCode::Escape) {
+            context.pop_scene_with(Transition::new(Effect::Fade(Color([0x00, 0x00, 0x00])), 30));
+        }
+        if context.input().keys().just_pressed(VirtualKeyCode::P) {
+            context.push_scene(Pause);
+        }
+        self.position = (self.position + 1) % 128;
+    }
+
+    fn render(&self, render: &mut Canvas<Color>) {
+        let mut render = render.painter();
+        render.clear(Color([0x00, 0x40, 0x00]));
+        render.circle_f((self.position, 64), 8, paint(Color([0xff, 0xff, 0x00])));
+    }
+}
+
+struct Pause;
+
+impl Scene<Config> for Pause {
+    fn update(&mut self, context: &mut Context<Config>) {
+        if context.input().keys().just_pressed(VirtualKeyCode::P) {
+            context.pop_scene();
+        }
+    }
+
+    fn render(&self, render: &mut Canvas<Color>) {
+        let mut render = render.painter();
+        render.rect_f((16, 16), (112, 112), |x, y, pixel| {
+            if (x + y) % 2 == 0 {
+                Color([0x00, 0x00, 0x00])
+            } else {
+                pixel
+            }
+        });
+        render.rect_f((56, 56), (62, 72), paint(Color([0xff, 0xff, 0xff])));
+        render.rect_f((66, 56), (72, 72), paint(Color([0xff, 0xff, 0xff])));
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Color([u8; 3]);
+
+struct Converter;
+
+impl color::Converter for Converter {
+    type Palette = Color;
+    fn convert(&self, color: &Self::Palette) -> u32 {
+        ((color.0[0] as u32) << 16) | ((color.0[1] as u32) << 8) | (color.0[2] as u32)
+    }
+}
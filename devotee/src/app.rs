@@ -12,6 +12,7 @@ use self::input::Input;
 use self::root::Root;
 use self::setup::Setup;
 use self::sound_system::SoundSystem;
+use crate::util::vector::Vector;
 use crate::visual::color::Converter;
 use crate::visual::Image;
 
@@ -21,8 +22,12 @@ pub mod config;
 pub mod context;
 /// User input handler.
 pub mod input;
+/// Post-processing of the output frame.
+pub mod post_process;
 /// The root node of the devotee app.
 pub mod root;
+/// Scene stack to be used as the root node.
+pub mod scene;
 /// Application launch setup.
 pub mod setup;
 /// `rodio`-based sound system.
@@ -58,6 +63,21 @@ where
     pause_on_focus_lost: bool,
 }
 
+impl<Cfg> Inner<Cfg>
+where
+    Cfg: Config,
+{
+    fn sync_resolution(&mut self) {
+        let current = self.render_target.dimensions();
+        let current = Vector::new(current.x() as u32, current.y() as u32);
+        let resolution = self.window.resolution();
+        if resolution != current && !Cfg::resize_render_target(&mut self.render_target, resolution)
+        {
+            self.window.revert_resolution(current);
+        }
+    }
+}
+
 impl<Cfg> App<Cfg>
 where
     Cfg: Config,
@@ -67,7 +87,8 @@ where
     /// Create an app with given `setup`.
     pub fn with_setup(setup: Setup<Cfg>) -> Option<Self> {
         let event_loop = EventLoop::new();
-        let window = window::Window::with_setup(&event_loop, &setup)?;
+        let mut setup = setup;
+        let window = window::Window::with_setup(&event_loop, &mut setup)?;
         let update_delay = setup.update_delay;
         let input = setup.input;
         let render_target = setup.render_target;
@@ -109,6 +130,7 @@ where
             window_commands: Vec::new(),
             sound_system: app.sound_system,
             converter: app.converter,
+            scene_commands: Vec::new(),
         };
 
         let mut node = (app.constructor)(&mut context);
@@ -121,6 +143,7 @@ where
         let mut paused = false;
 
         app.window.apply(&mut context.window_commands);
+        app.sync_resolution();
 
         event_loop.run(move |event, _, control_flow| match event {
             Event::NewEvents(StartCause::Init) => {
@@ -132,14 +155,19 @@ where
                 *control_flow = ControlFlow::WaitUntil(requested_resume + app.update_delay);
                 if !paused {
                     node.update(&mut context);
+                    // Only `SceneStack` consumes scene commands, drop the rest.
+                    context.scene_commands.clear();
 
                     if context.shall_stop() {
                         *control_flow = ControlFlow::Exit;
                     }
                     app.window.apply(&mut context.window_commands);
+                    app.sync_resolution();
 
                     context.input.next_frame();
+                    // Emitters are updated after the root, so it may move them and the listener freely.
                     if let Some(sound_system) = &mut context.sound_system {
+                        sound_system.update_emitters();
                         sound_system.clean_up_sinks();
                     }
                 }
@@ -168,6 +196,7 @@ where
                                 if app.window.resize_surface(width, height).is_none() {
                                     *control_flow = ControlFlow::Exit;
                                 }
+                                app.sync_resolution();
                             }
                         }
                         WindowEvent::Focused(focused) if app.pause_on_focus_lost => {
@@ -1,5 +1,6 @@
+use crate::util::vector::Vector;
 use crate::visual::color::Converter;
-use crate::visual::Image;
+use crate::visual::ImageMut;
 
 /// An application configuration.
 pub trait Config {
@@ -10,10 +11,18 @@ pub trait Config {
     /// The input handler.
     type Input;
     /// Render target to render to.
-    type RenderTarget: Image;
+    type RenderTarget: ImageMut;
 
     /// Provide palette converter.
     fn converter() -> Self::Converter;
     /// Provide default background color for the window.
     fn background_color() -> <Self::Converter as Converter>::Palette;
+    /// Resize render target to the new `resolution` requested by the window.
+    /// Returns `false` if the render target can not be resized, which is the default.
+    fn resize_render_target(
+        _render_target: &mut Self::RenderTarget,
+        _resolution: Vector<u32>,
+    ) -> bool {
+        false
+    }
 }
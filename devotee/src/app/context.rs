@@ -1,8 +1,11 @@
 use std::time::Duration;
 
 use super::config::Config;
+use super::scene::transition::Transition;
+use super::scene::{Scene, SceneCommand};
 use super::sound_system::SoundSystem;
 use super::window::{Window, WindowCommand};
+use crate::visual::Image;
 
 /// Context that stores various update-related data.
 pub struct Context<Cfg>
@@ -15,6 +18,7 @@ where
     pub(super) window_commands: Vec<WindowCommand>,
     pub(super) sound_system: Option<SoundSystem>,
     pub(super) converter: Cfg::Converter,
+    pub(super) scene_commands: Vec<SceneCommand<Cfg>>,
 }
 
 impl<Cfg> Context<Cfg>
@@ -45,6 +49,52 @@ where
         self.window_commands.push(Box::new(command))
     }
 
+    /// Push scene on top of the `SceneStack`.
+    /// Does nothing unless the root node is a `SceneStack`.
+    pub fn push_scene<S: 'static + Scene<Cfg>>(&mut self, scene: S) {
+        self.scene_commands
+            .push(SceneCommand::Push(Box::new(scene), None))
+    }
+
+    /// Push scene on top of the `SceneStack` with animated transition.
+    pub fn push_scene_with<S: 'static + Scene<Cfg>>(
+        &mut self,
+        scene: S,
+        transition: Transition<<Cfg::RenderTarget as Image>::Pixel>,
+    ) {
+        self.scene_commands
+            .push(SceneCommand::Push(Box::new(scene), Some(transition)))
+    }
+
+    /// Pop the top scene from the `SceneStack`.
+    /// Does nothing unless the root node is a `SceneStack`.
+    pub fn pop_scene(&mut self) {
+        self.scene_commands.push(SceneCommand::Pop(None))
+    }
+
+    /// Pop the top scene from the `SceneStack` with animated transition.
+    pub fn pop_scene_with(&mut self, transition: Transition<<Cfg::RenderTarget as Image>::Pixel>) {
+        self.scene_commands
+            .push(SceneCommand::Pop(Some(transition)))
+    }
+
+    /// Replace the top scene of the `SceneStack`.
+    /// Does nothing unless the root node is a `SceneStack`.
+    pub fn replace_scene<S: 'static + Scene<Cfg>>(&mut self, scene: S) {
+        self.scene_commands
+            .push(SceneCommand::Replace(Box::new(scene), None))
+    }
+
+    /// Replace the top scene of the `SceneStack` with animated transition.
+    pub fn replace_scene_with<S: 'static + Scene<Cfg>>(
+        &mut self,
+        scene: S,
+        transition: Transition<<Cfg::RenderTarget as Image>::Pixel>,
+    ) {
+        self.scene_commands
+            .push(SceneCommand::Replace(Box::new(scene), Some(transition)))
+    }
+
     /// Get optional reference to the `SoundSystem`.
     pub fn sound_system(&mut self) -> Option<&SoundSystem> {
         self.sound_system.as_ref()
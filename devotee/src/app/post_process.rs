@@ -0,0 +1,107 @@
+pub use devotee_backend::{Frame, Placement};
+
+/// Post-processing effect applied to the output frame.
+pub type PostProcess = Box<dyn FnMut(&mut Frame)>;
+
+fn darken(color: u32, factor: f32) -> u32 {
+    let factor = factor.clamp(0.0, 1.0);
+    let channel = |shift: u32| ((((color >> shift) & 0xff) as f32 * factor) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+fn image_area(frame: &Frame) -> (i32, i32, i32, i32) {
+    let placement = frame.placement();
+    let (x, y) = placement.origin();
+    let (width, height) = placement.size();
+    (
+        x.max(0),
+        y.max(0),
+        (x + width as i32).min(frame.width() as i32),
+        (y + height as i32).min(frame.height() as i32),
+    )
+}
+
+fn darken_at(frame: &mut Frame, x: i32, y: i32, factor: f32) {
+    let width = frame.width() as i32;
+    let pixel = &mut frame.pixels_mut()[(x + y * width) as usize];
+    *pixel = darken(*pixel, factor);
+}
+
+/// Darken every last frame row of each image pixel row by `darkness` in `[0.0; 1.0]`.
+/// Darken every odd row when the output is not upscaled at least twice.
+pub fn scanlines(darkness: f32) -> impl FnMut(&mut Frame) {
+    move |frame| {
+        let (from_x, from_y, to_x, to_y) = image_area(frame);
+        let placement = frame.placement();
+        let is_upscaled = placement.size().1 >= placement.resolution().1 * 2;
+        for y in from_y..to_y {
+            let is_dark = if is_upscaled {
+                placement.row(y) != placement.row(y + 1)
+            } else {
+                (y - from_y) % 2 == 1
+            };
+            if is_dark {
+                for x in from_x..to_x {
+                    darken_at(frame, x, y, 1.0 - darkness);
+                }
+            }
+        }
+    }
+}
+
+/// Darken the last frame row and column of each image pixel by `darkness` in `[0.0; 1.0]`.
+/// Works only when the output is upscaled at least twice.
+pub fn grid(darkness: f32) -> impl FnMut(&mut Frame) {
+    move |frame| {
+        let placement = frame.placement();
+        let (width, height) = placement.size();
+        let resolution = placement.resolution();
+        if width < resolution.0 * 2 || height < resolution.1 * 2 {
+            return;
+        }
+        let (from_x, from_y, to_x, to_y) = image_area(frame);
+        for y in from_y..to_y {
+            let is_edge_row = placement.row(y) != placement.row(y + 1);
+            for x in from_x..to_x {
+                if is_edge_row || placement.column(x) != placement.column(x + 1) {
+                    darken_at(frame, x, y, 1.0 - darkness);
+                }
+            }
+        }
+    }
+}
+
+/// Darken the image towards its corners by `strength` in `[0.0; 1.0]`.
+pub fn vignette(strength: f32) -> impl FnMut(&mut Frame) {
+    move |frame| {
+        let placement = frame.placement();
+        let (x, y) = placement.origin();
+        let (width, height) = placement.size();
+        let center_x = x as f32 + width as f32 / 2.0;
+        let center_y = y as f32 + height as f32 / 2.0;
+        let half_width = (width as f32 / 2.0).max(1.0);
+        let half_height = (height as f32 / 2.0).max(1.0);
+        let (from_x, from_y, to_x, to_y) = image_area(frame);
+        for y in from_y..to_y {
+            let dy = (y as f32 + 0.5 - center_y) / half_height;
+            for x in from_x..to_x {
+                let dx = (x as f32 + 0.5 - center_x) / half_width;
+                let distance = (dx * dx + dy * dy) / 2.0;
+                darken_at(frame, x, y, 1.0 - strength * distance);
+            }
+        }
+    }
+}
+
+/// Remap every frame pixel with the `function`.
+/// Both input and output are `0x00rrggbb` values.
+pub fn grade<F>(mut function: F) -> impl FnMut(&mut Frame)
+where
+    F: FnMut(u32) -> u32,
+{
+    move |frame| {
+        for pixel in frame.pixels_mut() {
+            *pixel = function(*pixel);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::cell::RefCell;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
+use self::transition::Transition;
+use super::config::Config;
+use super::context::Context;
+use super::root::Root;
+use crate::visual::{Image, ImageMut};
+
+/// Animated transitions between scenes.
+pub mod transition;
+
+type Pixel<Cfg> = <<Cfg as Config>::RenderTarget as Image>::Pixel;
+
+/// Single scene of the `SceneStack`.
+/// Handles update and render logic while being on top of the stack.
+pub trait Scene<Cfg>
+where
+    Cfg: Config,
+{
+    /// Update mutably.
+    fn update(&mut self, context: &mut Context<Cfg>);
+
+    /// Perform render on provided `RenderTarget`.
+    fn render(&self, render: &mut Cfg::RenderTarget);
+
+    /// Handle being placed on the stack.
+    fn enter(&mut self, _context: &mut Context<Cfg>) {}
+
+    /// Handle being removed from the stack.
+    fn exit(&mut self, _context: &mut Context<Cfg>) {}
+
+    /// Handle being covered by another scene.
+    fn pause(&mut self, _context: &mut Context<Cfg>) {}
+
+    /// Handle becoming the top scene again.
+    fn resume(&mut self, _context: &mut Context<Cfg>) {}
+
+    /// Check if scenes below this one should be rendered before it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+pub(super) enum SceneCommand<Cfg>
+where
+    Cfg: Config,
+{
+    Push(Box<dyn Scene<Cfg>>, Option<Transition<Pixel<Cfg>>>),
+    Pop(Option<Transition<Pixel<Cfg>>>),
+    Replace(Box<dyn Scene<Cfg>>, Option<Transition<Pixel<Cfg>>>),
+}
+
+struct Active<Cfg>
+where
+    Cfg: Config,
+{
+    transition: Transition<Pixel<Cfg>>,
+    frame: u32,
+    // Scene removed from the stack, it is still rendered during the transition.
+    leaving: Option<Box<dyn Scene<Cfg>>>,
+    // Count of scenes on the stack being part of the old render.
+    below: usize,
+}
+
+/// Stack of scenes to be used as the root node.
+/// Only the top scene is updated.
+/// Scenes are pushed, popped and replaced through the `Context`.
+/// The application is stopped once the stack gets empty.
+///
+/// Scene changes may be accompanied by a `Transition`.
+/// Both old and new scenes are rendered until the transition is over.
+pub struct SceneStack<Cfg>
+where
+    Cfg: Config,
+{
+    scenes: Vec<Box<dyn Scene<Cfg>>>,
+    active: Option<Active<Cfg>>,
+    scratch: RefCell<Option<Cfg::RenderTarget>>,
+}
+
+impl<Cfg> SceneStack<Cfg>
+where
+    Cfg: Config,
+{
+    /// Create new empty scene stack.
+    /// Initial scene is expected to be pushed through the `Context`.
+    pub fn new() -> Self {
+        let scenes = Vec::new();
+        let active = None;
+        let scratch = RefCell::new(None);
+        Self {
+            scenes,
+            active,
+            scratch,
+        }
+    }
+
+    /// Get count of scenes on the stack.
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// Check if the stack is empty.
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Check if there is a transition in progress.
+    pub fn is_transitioning(&self) -> bool {
+        self.active.is_some()
+    }
+
+    fn apply(&mut self, context: &mut Context<Cfg>) {
+        while !context.scene_commands.is_empty() {
+            for command in mem::take(&mut context.scene_commands) {
+                match command {
+                    SceneCommand::Push(mut scene, transition) => {
+                        let below = self.scenes.len();
+                        if let Some(top) = self.scenes.last_mut() {
+                            top.pause(context);
+                        }
+                        scene.enter(context);
+                        self.scenes.push(scene);
+                        self.start(transition, None, below);
+                    }
+                    SceneCommand::Pop(transition) => {
+                        let leaving = self.scenes.pop().map(|mut scene| {
+                            scene.exit(context);
+                            scene
+                        });
+                        if let Some(top) = self.scenes.last_mut() {
+                            top.resume(context);
+                        }
+                        let below = self.scenes.len();
+                        self.start(transition, leaving, below);
+                    }
+                    SceneCommand::Replace(mut scene, transition) => {
+                        let leaving = self.scenes.pop().map(|mut previous| {
+                            previous.exit(context);
+                            previous
+                        });
+                        let below = self.scenes.len();
+                        scene.enter(context);
+                        self.scenes.push(scene);
+                        self.start(transition, leaving, below);
+                    }
+                }
+            }
+        }
+    }
+
+    fn start(
+        &mut self,
+        transition: Option<Transition<Pixel<Cfg>>>,
+        leaving: Option<Box<dyn Scene<Cfg>>>,
+        below: usize,
+    ) {
+        if let Some(transition) = transition.filter(|transition| transition.frames() > 0) {
+            self.active = Some(Active {
+                transition,
+                frame: 0,
+                leaving,
+                below,
+            });
+        } else if let Some(active) = &mut self.active {
+            // Scenes popped during the transition are no longer part of the old render.
+            active.below = active.below.min(self.scenes.len());
+        }
+    }
+
+    fn advance(&mut self) {
+        if let Some(active) = &mut self.active {
+            active.frame += 1;
+            if active.frame >= active.transition.frames() {
+                self.active = None;
+            }
+        }
+    }
+
+    fn render_layers<'a, L>(layers: L, render: &mut Cfg::RenderTarget)
+    where
+        L: Iterator<Item = &'a dyn Scene<Cfg>>,
+        Cfg: 'a,
+    {
+        let layers = layers.collect::<Vec<_>>();
+        let start = layers
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in layers[start..].iter() {
+            scene.render(render);
+        }
+    }
+}
+
+impl<Cfg> Default for SceneStack<Cfg>
+where
+    Cfg: Config,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Cfg> Root<Cfg> for SceneStack<Cfg>
+where
+    Cfg: Config,
+    Cfg::RenderTarget: Clone,
+    Pixel<Cfg>: Clone,
+    for<'a> <Cfg::RenderTarget as Image>::PixelRef<'a>: Deref<Target = Pixel<Cfg>>,
+    for<'a> <Cfg::RenderTarget as ImageMut>::PixelMut<'a>: DerefMut<Target = Pixel<Cfg>>,
+{
+    fn update(&mut self, update: &mut Context<Cfg>) {
+        self.advance();
+        self.apply(update);
+        if let Some(top) = self.scenes.last_mut() {
+            top.update(update);
+        }
+        self.apply(update);
+        if self.scenes.is_empty() {
+            update.shutdown();
+        }
+    }
+
+    fn render(&self, render: &mut Cfg::RenderTarget) {
+        let scenes = self.scenes.iter().map(AsRef::as_ref);
+        if let Some(active) = &self.active {
+            let mut scratch = self.scratch.borrow_mut();
+            if scratch.as_ref().map(Image::dimensions) != Some(render.dimensions()) {
+                *scratch = None;
+            }
+            let scratch = scratch.get_or_insert_with(|| render.clone());
+            let below = self.scenes[..active.below].iter().map(AsRef::as_ref);
+            Self::render_layers(below.chain(active.leaving.as_deref()), scratch);
+            Self::render_layers(scenes, render);
+            let progress = active.frame as f32 / active.transition.frames() as f32;
+            active.transition.blend(progress, scratch, render);
+        } else {
+            Self::render_layers(scenes, render);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::transition::Effect;
+    use super::*;
+    use crate::visual::canvas::Canvas;
+    use crate::visual::color::Identity;
+
+    struct TestConfig;
+
+    impl Config for TestConfig {
+        type Root = SceneStack<Self>;
+        type Converter = Identity<u32>;
+        type Input = ();
+        type RenderTarget = Canvas<u32>;
+
+        fn converter() -> Self::Converter {
+            Identity::new()
+        }
+
+        fn background_color() -> u32 {
+            0
+        }
+    }
+
+    struct Flat(u32);
+
+    impl Scene<TestConfig> for Flat {
+        fn update(&mut self, _context: &mut Context<TestConfig>) {}
+
+        fn render(&self, render: &mut Canvas<u32>) {
+            render.clear(self.0);
+        }
+    }
+
+    fn context() -> Context<TestConfig> {
+        Context {
+            delta: Duration::ZERO,
+            input: (),
+            shall_stop: false,
+            window_commands: Vec::new(),
+            sound_system: None,
+            converter: Identity::new(),
+            scene_commands: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pop_during_transition_shrinks_old_render() {
+        let mut context = context();
+        let mut stack = SceneStack::new();
+        context.push_scene(Flat(1));
+        context.push_scene(Flat(2));
+        context.push_scene(Flat(3));
+        stack.apply(&mut context);
+
+        context.pop_scene_with(Transition::new(Effect::Fade(0), 8));
+        stack.apply(&mut context);
+        context.pop_scene();
+        stack.apply(&mut context);
+
+        assert_eq!(stack.len(), 1);
+        assert!(stack.is_transitioning());
+        let mut render = Canvas::with_resolution(0, 4, 4);
+        stack.render(&mut render);
+    }
+}
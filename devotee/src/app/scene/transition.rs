@@ -0,0 +1,132 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::util::vector::Vector;
+use crate::visual::dither::bayer;
+use crate::visual::ImageMut;
+
+const PIXELATE_BLOCK: f32 = 16.0;
+
+/// Visual effect of the scene transition.
+#[derive(Clone, Copy, Debug)]
+pub enum Effect<P> {
+    /// Dithered fade of the old scene into the color and of the color into the new scene.
+    Fade(P),
+    /// New scene is revealed from left to right.
+    HorizontalWipe,
+    /// New scene is revealed from top to bottom.
+    VerticalWipe,
+    /// New scene is revealed inside a circle growing from the center.
+    CircleWipe,
+    /// Old scene dissolves into the new one with ordered dithering.
+    Dissolve,
+    /// Old scene gets pixelated, new one gets unpixelated.
+    Pixelate,
+}
+
+/// Animated transition between two renders.
+#[derive(Clone, Copy, Debug)]
+pub struct Transition<P> {
+    effect: Effect<P>,
+    frames: u32,
+}
+
+impl<P> Transition<P> {
+    /// Create new transition with given effect lasting for given count of update frames.
+    pub fn new(effect: Effect<P>, frames: u32) -> Self {
+        Self { effect, frames }
+    }
+
+    /// Get effect of this transition.
+    pub fn effect(&self) -> &Effect<P> {
+        &self.effect
+    }
+
+    /// Get count of update frames this transition lasts for.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+}
+
+impl<P> Transition<P>
+where
+    P: Clone,
+{
+    /// Blend `from` image into `into` image according to the `progress` in `[0.0, 1.0]` range.
+    /// The result is stored in the `into` image.
+    pub fn blend<I>(&self, progress: f32, from: &I, into: &mut I)
+    where
+        I: ImageMut<Pixel = P>,
+        for<'a> I::PixelRef<'a>: Deref<Target = P>,
+        for<'a> I::PixelMut<'a>: DerefMut<Target = P>,
+    {
+        let progress = progress.clamp(0.0, 1.0);
+        let width = into.width().min(from.width());
+        let height = into.height().min(from.height());
+        let center = Vector::new(width as f32, height as f32) / 2.0;
+        let radius = center.dot(center).sqrt() * progress;
+
+        for y in 0..height {
+            for x in 0..width {
+                let position = Vector::new(x, y);
+                // SAFETY: `x` and `y` are in bounds of both images.
+                unsafe {
+                    let pixel = match &self.effect {
+                        Effect::Fade(color) => {
+                            let (level, source) = if progress < 0.5 {
+                                (progress * 2.0, from.unsafe_pixel(position).clone())
+                            } else {
+                                ((1.0 - progress) * 2.0, into.unsafe_pixel(position).clone())
+                            };
+                            if bayer(x, y) < level {
+                                color.clone()
+                            } else {
+                                source
+                            }
+                        }
+                        Effect::HorizontalWipe => {
+                            if (x as f32) < width as f32 * progress {
+                                continue;
+                            }
+                            from.unsafe_pixel(position).clone()
+                        }
+                        Effect::VerticalWipe => {
+                            if (y as f32) < height as f32 * progress {
+                                continue;
+                            }
+                            from.unsafe_pixel(position).clone()
+                        }
+                        Effect::CircleWipe => {
+                            let delta = Vector::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+                            if delta.dot(delta).sqrt() < radius {
+                                continue;
+                            }
+                            from.unsafe_pixel(position).clone()
+                        }
+                        Effect::Dissolve => {
+                            if bayer(x, y) < progress {
+                                continue;
+                            }
+                            from.unsafe_pixel(position).clone()
+                        }
+                        Effect::Pixelate => {
+                            let (level, old) = if progress < 0.5 {
+                                (progress * 2.0, true)
+                            } else {
+                                ((1.0 - progress) * 2.0, false)
+                            };
+                            let block = 1 + (level * PIXELATE_BLOCK) as i32;
+                            let origin = Vector::new(x - x % block, y - y % block);
+                            if old {
+                                from.unsafe_pixel(origin).clone()
+                            } else {
+                                // Block origin is processed before the rest of the block and keeps its value.
+                                into.unsafe_pixel(origin).clone()
+                            }
+                        }
+                    };
+                    *into.unsafe_pixel_mut(position) = pixel;
+                }
+            }
+        }
+    }
+}
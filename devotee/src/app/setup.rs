@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use super::config::Config;
 use super::context::Context;
+use super::post_process::{Frame, PostProcess};
+use super::window::Scaling;
 use super::Constructor;
+use crate::util::vector::Vector;
 
 /// Application setup structure.
 /// Describes root node, title, pause behavior, etc.
@@ -14,12 +17,15 @@ where
     pub(super) update_delay: Duration,
     pub(super) fullscreen: bool,
     pub(super) scale: u32,
+    pub(super) scaling: Scaling,
+    pub(super) min_size: Option<Vector<u32>>,
     pub(super) render_target: Cfg::RenderTarget,
     pub(super) constructor: Constructor<Cfg::Root, Cfg>,
     #[cfg(target_arch = "wasm32")]
     pub(super) element_id: Option<&'static str>,
     pub(super) pause_on_focus_lost: bool,
     pub(super) input: Cfg::Input,
+    pub(super) post_process: Vec<PostProcess>,
 }
 
 impl<Cfg> Setup<Cfg>
@@ -42,12 +48,15 @@ where
             update_delay,
             fullscreen,
             scale,
+            scaling: Scaling::default(),
+            min_size: None,
             render_target,
             constructor,
             #[cfg(target_arch = "wasm32")]
             element_id: None,
             pause_on_focus_lost: true,
             input,
+            post_process: Vec::new(),
         }
     }
 
@@ -64,6 +73,18 @@ where
         Self { scale, ..self }
     }
 
+    /// Set scaling of the image to the window.
+    pub fn with_scaling(self, scaling: Scaling) -> Self {
+        Self { scaling, ..self }
+    }
+
+    /// Set minimal inner size of the window.
+    /// Defaults to the render target resolution.
+    pub fn with_min_size(self, min_size: Vector<u32>) -> Self {
+        let min_size = Some(min_size);
+        Self { min_size, ..self }
+    }
+
     /// Set fullscreen option.
     pub fn with_fullscreen(self, fullscreen: bool) -> Self {
         Self { fullscreen, ..self }
@@ -91,4 +112,14 @@ where
             ..self
         }
     }
+
+    /// Add post-processing effect to the end of the chain.
+    /// The chain is applied to the output frame in order of addition.
+    pub fn with_post_process<F>(mut self, post_process: F) -> Self
+    where
+        F: 'static + FnMut(&mut Frame),
+    {
+        self.post_process.push(Box::new(post_process));
+        self
+    }
 }
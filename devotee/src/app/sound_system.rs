@@ -1,14 +1,22 @@
+use std::cell::Cell;
 use std::mem;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use rodio::source::Source;
+use rodio::source::{ChannelVolume, Source};
 use rodio::{OutputStream, OutputStreamHandle, Sink};
 
+use crate::util::vector::Vector;
+
 pub use rodio;
 
 /// `rodio`'s `Sink` wrapped in reference counter.
 pub type Sound = Rc<Sink>;
 
+/// Positional `Emitter` wrapped in reference counter.
+pub type PositionalSound = Rc<Emitter>;
+
 /// `rodio`-based sound system,
 pub struct SoundSystem {
     // We are storing `OutputStream` instance to save it from being dropped and thus stopping sound.
@@ -16,16 +24,25 @@ pub struct SoundSystem {
     output_stream: OutputStream,
     handle: OutputStreamHandle,
     sinks: Vec<Rc<Sink>>,
+    emitters: Vec<Rc<Emitter>>,
+    listener: Vector<i32>,
+    panning: f32,
 }
 
 impl SoundSystem {
     pub(crate) fn try_new() -> Option<Self> {
         let (output_stream, handle) = OutputStream::try_default().ok()?;
         let sinks = Vec::new();
+        let emitters = Vec::new();
+        let listener = Vector::new(0, 0);
+        let panning = 128.0;
         Some(Self {
             output_stream,
             handle,
             sinks,
+            emitters,
+            listener,
+            panning,
         })
     }
 
@@ -47,22 +64,291 @@ impl SoundSystem {
         }
     }
 
+    /// Play given sound at `position` in canvas space.
+    /// Returns `None` in fail case.
+    /// The returned emitter may be moved around and used to stop sound.
+    pub fn play_at(
+        &mut self,
+        source: Box<dyn Source<Item = f32> + Send>,
+        position: Vector<i32>,
+        rolloff: Rolloff,
+    ) -> Option<Rc<Emitter>> {
+        let sink = self.sink()?;
+        let [left, right] = self.volumes(position, rolloff);
+        let volumes = Arc::new(Mutex::new([left, right]));
+        let shared = volumes.clone();
+        let source = ChannelVolume::new(source, vec![left, right]).periodic_access(
+            Duration::from_millis(10),
+            move |source| {
+                if let Ok(volumes) = shared.lock() {
+                    source.set_volume(0, volumes[0]);
+                    source.set_volume(1, volumes[1]);
+                }
+            },
+        );
+        sink.append(source);
+        let emitter = Rc::new(Emitter {
+            sink,
+            position: Cell::new(position),
+            rolloff: Cell::new(rolloff),
+            volumes,
+        });
+        self.emitters.push(emitter.clone());
+        Some(emitter)
+    }
+
+    /// Get listener position in canvas space.
+    pub fn listener(&self) -> Vector<i32> {
+        self.listener
+    }
+
+    /// Set listener position in canvas space.
+    pub fn set_listener(&mut self, listener: Vector<i32>) {
+        self.listener = listener;
+    }
+
+    /// Get horizontal distance at which the sound is panned to a single channel.
+    pub fn panning(&self) -> f32 {
+        self.panning
+    }
+
+    /// Set horizontal distance at which the sound is panned to a single channel.
+    /// Non-positive value disables panning.
+    pub fn set_panning(&mut self, panning: f32) {
+        self.panning = panning;
+    }
+
+    fn volumes(&self, position: Vector<i32>, rolloff: Rolloff) -> [f32; 2] {
+        volumes(position - self.listener, rolloff, self.panning)
+    }
+
+    pub(super) fn update_emitters(&mut self) {
+        for emitter in self.emitters.iter() {
+            let volumes = self.volumes(emitter.position(), emitter.rolloff());
+            if let Ok(mut shared) = emitter.volumes.lock() {
+                *shared = volumes;
+            }
+        }
+    }
+
     pub(super) fn clean_up_sinks(&mut self) {
         self.sinks = mem::take(&mut self.sinks)
             .into_iter()
             .filter(|sink| !sink.empty())
             .collect();
+        self.emitters = mem::take(&mut self.emitters)
+            .into_iter()
+            .filter(|emitter| !emitter.sink.empty())
+            .collect();
     }
 
     pub(super) fn pause(&self) {
         for sink in self.sinks.iter() {
             sink.pause();
         }
+        for emitter in self.emitters.iter() {
+            emitter.sink.pause();
+        }
     }
 
     pub(super) fn resume(&self) {
         for sink in self.sinks.iter() {
             sink.play();
         }
+        for emitter in self.emitters.iter() {
+            emitter.sink.play();
+        }
+    }
+}
+
+// Get left and right channel volumes of the sound at `delta` from the listener.
+fn volumes(delta: Vector<i32>, rolloff: Rolloff, panning: f32) -> [f32; 2] {
+    let delta = delta.map(|value| value as f32);
+    let gain = rolloff.gain(delta.dot(delta).sqrt());
+    let pan = if panning > 0.0 {
+        (delta.x() / panning).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    [gain * (1.0 - pan).min(1.0), gain * (1.0 + pan).min(1.0)]
+}
+
+/// Sound emitter placed in canvas space.
+/// Its volume and panning are recalculated relative to the listener right after every `Root::update`,
+/// so positions set during the update are heard on the same tick.
+pub struct Emitter {
+    sink: Sink,
+    position: Cell<Vector<i32>>,
+    rolloff: Cell<Rolloff>,
+    volumes: Arc<Mutex<[f32; 2]>>,
+}
+
+impl Emitter {
+    /// Get emitter position in canvas space.
+    pub fn position(&self) -> Vector<i32> {
+        self.position.get()
+    }
+
+    /// Set emitter position in canvas space.
+    pub fn set_position(&self, position: Vector<i32>) {
+        self.position.set(position);
+    }
+
+    /// Get distance attenuation of this emitter.
+    pub fn rolloff(&self) -> Rolloff {
+        self.rolloff.get()
+    }
+
+    /// Set distance attenuation of this emitter.
+    pub fn set_rolloff(&self, rolloff: Rolloff) {
+        self.rolloff.set(rolloff);
+    }
+
+    /// Get reference to the underlying sink.
+    /// It may be used to stop sound or to change its volume.
+    pub fn sink(&self) -> &Sink {
+        &self.sink
+    }
+}
+
+/// Distance attenuation curve for positional sounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Rolloff {
+    /// No attenuation at all.
+    #[default]
+    None,
+    /// Volume falls linearly from full at `near` distance to silence at `far` distance.
+    Linear {
+        /// Distance to start attenuation at.
+        near: f32,
+        /// Distance of complete silence.
+        far: f32,
+    },
+    /// Volume falls as `near / distance` after `near` distance and is cut at `far` distance.
+    Inverse {
+        /// Distance to start attenuation at.
+        near: f32,
+        /// Distance of complete silence.
+        far: f32,
+    },
+    /// Volume falls as `(near / distance)^2` after `near` distance and is cut at `far` distance.
+    InverseSquare {
+        /// Distance to start attenuation at.
+        near: f32,
+        /// Distance of complete silence.
+        far: f32,
+    },
+}
+
+impl Rolloff {
+    /// Calculate volume multiplier for given distance.
+    pub fn gain(&self, distance: f32) -> f32 {
+        match *self {
+            Rolloff::None => 1.0,
+            Rolloff::Linear { near, far } => {
+                if distance <= near {
+                    1.0
+                } else if distance >= far {
+                    0.0
+                } else {
+                    (far - distance) / (far - near)
+                }
+            }
+            Rolloff::Inverse { near, far } => {
+                if distance <= near {
+                    1.0
+                } else if distance >= far {
+                    0.0
+                } else {
+                    near / distance
+                }
+            }
+            Rolloff::InverseSquare { near, far } => {
+                if distance <= near {
+                    1.0
+                } else if distance >= far {
+                    0.0
+                } else {
+                    (near / distance).powi(2)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEAR: f32 = 10.0;
+    const FAR: f32 = 100.0;
+
+    fn rolloffs() -> [Rolloff; 3] {
+        [
+            Rolloff::Linear {
+                near: NEAR,
+                far: FAR,
+            },
+            Rolloff::Inverse {
+                near: NEAR,
+                far: FAR,
+            },
+            Rolloff::InverseSquare {
+                near: NEAR,
+                far: FAR,
+            },
+        ]
+    }
+
+    #[test]
+    fn gain_is_full_up_to_near_distance() {
+        for rolloff in rolloffs() {
+            assert_eq!(rolloff.gain(0.0), 1.0, "{:?}", rolloff);
+            assert_eq!(rolloff.gain(NEAR), 1.0, "{:?}", rolloff);
+        }
+    }
+
+    #[test]
+    fn gain_is_silent_from_far_distance() {
+        for rolloff in rolloffs() {
+            assert_eq!(rolloff.gain(FAR), 0.0, "{:?}", rolloff);
+            assert_eq!(rolloff.gain(FAR * 2.0), 0.0, "{:?}", rolloff);
+        }
+    }
+
+    #[test]
+    fn gain_falls_between_near_and_far_distance() {
+        let [linear, inverse, inverse_square] = rolloffs();
+        assert_eq!(linear.gain(55.0), 0.5);
+        assert_eq!(inverse.gain(20.0), 0.5);
+        assert_eq!(inverse_square.gain(20.0), 0.25);
+        for rolloff in rolloffs() {
+            assert!(rolloff.gain(30.0) > rolloff.gain(60.0), "{:?}", rolloff);
+        }
+    }
+
+    #[test]
+    fn no_rolloff_keeps_full_gain() {
+        for distance in [0.0, NEAR, FAR, FAR * 2.0] {
+            assert_eq!(Rolloff::None.gain(distance), 1.0);
+        }
+    }
+
+    #[test]
+    fn pans_towards_emitter_side() {
+        let [left, right] = volumes(Vector::new(-64, 0), Rolloff::None, 128.0);
+        assert_eq!([left, right], [1.0, 0.5]);
+        let [left, right] = volumes(Vector::new(64, 0), Rolloff::None, 128.0);
+        assert_eq!([left, right], [0.5, 1.0]);
+        let [left, right] = volumes(Vector::new(0, 64), Rolloff::None, 128.0);
+        assert_eq!([left, right], [1.0, 1.0]);
+        let [left, right] = volumes(Vector::new(512, 0), Rolloff::None, 128.0);
+        assert_eq!([left, right], [0.0, 1.0]);
+    }
+
+    #[test]
+    fn zero_panning_keeps_center() {
+        let [left, right] = volumes(Vector::new(64, 0), Rolloff::None, 0.0);
+        assert_eq!([left, right], [1.0, 1.0]);
     }
 }
@@ -11,12 +11,14 @@ use devotee_backend_pixels::PixelsBackend;
 #[cfg(feature = "back-softbuffer")]
 use devotee_backend_softbuffer::SoftbufferBackend;
 
+use super::post_process::{Frame, PostProcess};
 use super::{Config, Setup};
 use crate::util::vector::Vector;
 use crate::visual::color::Converter;
 use crate::visual::Image;
 
 pub use devotee_backend::winit;
+pub use devotee_backend::Scaling;
 
 pub(super) type WindowCommand = Box<dyn FnOnce(&mut Window)>;
 
@@ -31,11 +33,17 @@ pub struct Window {
     window: WinitWindow,
     back: Back,
     resolution: Vector<u32>,
+    expand: Option<u32>,
     background: u32,
+    scaling: Scaling,
+    post_process: Vec<PostProcess>,
 }
 
 impl Window {
-    pub(super) fn with_setup<Cfg>(event_loop: &EventLoop<()>, setup: &Setup<Cfg>) -> Option<Self>
+    pub(super) fn with_setup<Cfg>(
+        event_loop: &EventLoop<()>,
+        setup: &mut Setup<Cfg>,
+    ) -> Option<Self>
     where
         Cfg: Config,
         Cfg::RenderTarget: Image,
@@ -52,10 +60,11 @@ impl Window {
             return None;
         }
         let window_size = resolution * setup.scale;
+        let min_size = setup.min_size.unwrap_or(resolution);
 
         let window = {
             let builder = WindowBuilder::new()
-                .with_min_inner_size(PhysicalSize::new(resolution.x(), resolution.y()))
+                .with_min_inner_size(PhysicalSize::new(min_size.x(), min_size.y()))
                 .with_inner_size(PhysicalSize::new(window_size.x(), window_size.y()))
                 .with_fullscreen(if setup.fullscreen {
                     Some(Fullscreen::Borderless(None))
@@ -84,11 +93,16 @@ impl Window {
 
         let background = Cfg::converter().convert(&Cfg::background_color());
 
+        let post_process = std::mem::take(&mut setup.post_process);
+
         Some(Window {
             window,
             back,
             resolution,
+            expand: None,
             background,
+            scaling: setup.scaling,
+            post_process,
         })
     }
 
@@ -97,7 +111,9 @@ impl Window {
     }
 
     pub(super) fn resize_surface(&mut self, width: NonZeroU32, height: NonZeroU32) -> Option<()> {
-        self.back.resize(width, height)
+        self.back.resize(width, height)?;
+        self.expand_resolution();
+        Some(())
     }
 
     /// Get window pixel resolution.
@@ -105,6 +121,42 @@ impl Window {
         self.resolution
     }
 
+    /// Set window pixel resolution.
+    /// The render target is resized accordingly with `Config::resize_render_target`.
+    /// Disables expanding to the window size.
+    /// Zero-sized resolution is ignored.
+    pub fn set_resolution(&mut self, resolution: Vector<u32>) {
+        self.expand = None;
+        if resolution.x() > 0 && resolution.y() > 0 {
+            self.resolution = resolution;
+        }
+    }
+
+    /// Make resolution follow the window size divided by `scale`.
+    /// Pass `None` to keep the current resolution fixed.
+    pub fn set_expand(&mut self, scale: Option<u32>) {
+        self.expand = scale.filter(|scale| *scale > 0);
+        self.expand_resolution();
+    }
+
+    /// Get the scale of expanding to the window size, if enabled.
+    pub fn expand(&self) -> Option<u32> {
+        self.expand
+    }
+
+    fn expand_resolution(&mut self) {
+        if let Some(scale) = self.expand {
+            let size = self.window.inner_size();
+            self.resolution =
+                Vector::new((size.width / scale).max(1), (size.height / scale).max(1));
+        }
+    }
+
+    pub(super) fn revert_resolution(&mut self, resolution: Vector<u32>) {
+        self.expand = None;
+        self.resolution = resolution;
+    }
+
     /// Set window to fullscreen mode.
     pub fn set_fullscreen(&mut self, fullscreen: bool) {
         if fullscreen {
@@ -120,6 +172,29 @@ impl Window {
         self.window.fullscreen().is_some()
     }
 
+    /// Get scaling of the image to the window.
+    pub fn scaling(&self) -> Scaling {
+        self.scaling
+    }
+
+    /// Set scaling of the image to the window.
+    pub fn set_scaling(&mut self, scaling: Scaling) {
+        self.scaling = scaling;
+    }
+
+    /// Add post-processing effect to the end of the chain.
+    pub fn add_post_process<F>(&mut self, post_process: F)
+    where
+        F: 'static + FnMut(&mut Frame),
+    {
+        self.post_process.push(Box::new(post_process));
+    }
+
+    /// Remove all post-processing effects.
+    pub fn clear_post_process(&mut self) {
+        self.post_process.clear();
+    }
+
     pub(super) fn apply(&mut self, commands: &mut Vec<WindowCommand>) {
         for command in commands.drain(..) {
             command(self)
@@ -134,8 +209,30 @@ impl Window {
     where
         I: Iterator<Item = &'a P>,
     {
-        self.back
-            .draw_image(image, converter, &self.window, self.background)
+        if self.post_process.is_empty() {
+            return self.back.draw_image(
+                image,
+                converter,
+                &self.window,
+                self.background,
+                self.scaling,
+                None,
+            );
+        }
+        let chain = &mut self.post_process;
+        let mut post_process = |frame: &mut Frame| {
+            for effect in chain.iter_mut() {
+                effect(frame);
+            }
+        };
+        self.back.draw_image(
+            image,
+            converter,
+            &self.window,
+            self.background,
+            self.scaling,
+            Some(&mut post_process),
+        )
     }
 
     /// Recalculate raw window position into camera-related.
@@ -144,7 +241,12 @@ impl Window {
         position: PhysicalPosition<f64>,
     ) -> Result<Vector<i32>, Vector<i32>> {
         self.back
-            .window_pos_to_inner(position, &self.window, self.resolution.split())
+            .window_pos_to_inner(
+                position,
+                &self.window,
+                self.resolution.split(),
+                self.scaling,
+            )
             .map(Into::into)
             .map_err(Into::into)
     }
@@ -1,3 +1,5 @@
+/// Two-dimensional affine transformation.
+pub mod affine;
 /// Collection of elements with `get` and `get_mut` operations.
 pub mod getter;
 /// Vector represents two-dimensional point in space.
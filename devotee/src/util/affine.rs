@@ -0,0 +1,127 @@
+use super::vector::Vector;
+
+/// Two-dimensional affine transformation.
+/// It is a linear transformation followed by translation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    matrix: [[f32; 2]; 2],
+    translation: Vector<f32>,
+}
+
+impl Affine {
+    /// Create new transformation with given row-major `matrix` and `translation`.
+    pub const fn new(matrix: [[f32; 2]; 2], translation: Vector<f32>) -> Self {
+        Self {
+            matrix,
+            translation,
+        }
+    }
+
+    /// Create identity transformation.
+    pub const fn identity() -> Self {
+        Self::new([[1.0, 0.0], [0.0, 1.0]], Vector::new(0.0, 0.0))
+    }
+
+    /// Create rotation by `angle` in radians.
+    /// With `y` axis pointing down positive angle rotates clockwise.
+    pub fn rotation(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new([[cos, -sin], [sin, cos]], Vector::new(0.0, 0.0))
+    }
+
+    /// Create non-uniform scale.
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new([[x, 0.0], [0.0, y]], Vector::new(0.0, 0.0))
+    }
+
+    /// Create shear with `x` shifted by `y` multiplied by `x` factor and vice versa.
+    pub fn shear(x: f32, y: f32) -> Self {
+        Self::new([[1.0, x], [y, 1.0]], Vector::new(0.0, 0.0))
+    }
+
+    /// Create translation by `offset`.
+    pub fn translation(offset: Vector<f32>) -> Self {
+        Self::new([[1.0, 0.0], [0.0, 1.0]], offset)
+    }
+
+    /// Get row-major linear part of this transformation.
+    pub fn matrix(&self) -> [[f32; 2]; 2] {
+        self.matrix
+    }
+
+    /// Get translation part of this transformation.
+    pub fn offset(&self) -> Vector<f32> {
+        self.translation
+    }
+
+    /// Combine this transformation with the `next` one applied after it.
+    pub fn then(self, next: Affine) -> Self {
+        let [[a, b], [c, d]] = next.matrix;
+        let [[e, f], [g, h]] = self.matrix;
+        let matrix = [
+            [a * e + b * g, a * f + b * h],
+            [c * e + d * g, c * f + d * h],
+        ];
+        let translation = next.apply(self.translation);
+        Self::new(matrix, translation)
+    }
+
+    /// Make this transformation keep the `pivot` point in place.
+    pub fn around(self, pivot: Vector<f32>) -> Self {
+        Affine::translation(-pivot)
+            .then(self)
+            .then(Affine::translation(pivot))
+    }
+
+    /// Apply this transformation to the `point`.
+    pub fn apply(&self, point: Vector<f32>) -> Vector<f32> {
+        let [[a, b], [c, d]] = self.matrix;
+        let (x, y) = point.split();
+        Vector::new(a * x + b * y, c * x + d * y) + self.translation
+    }
+
+    /// Get inverse transformation if there is one.
+    pub fn inverse(&self) -> Option<Self> {
+        let [[a, b], [c, d]] = self.matrix;
+        let determinant = a * d - b * c;
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let matrix = [
+            [d / determinant, -b / determinant],
+            [-c / determinant, a / determinant],
+        ];
+        let linear = Self::new(matrix, Vector::new(0.0, 0.0));
+        let translation = -linear.apply(self.translation);
+        let is_finite = matrix.iter().flatten().all(|value| value.is_finite())
+            && translation.x().is_finite()
+            && translation.y().is_finite();
+        is_finite.then(|| Self::new(matrix, translation))
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverts_tiny_scale() {
+        let affine = Affine::scale(0.0001, 0.0001).then(Affine::translation(Vector::new(1.0, 2.0)));
+        let inverse = affine.inverse().unwrap();
+        let point = inverse.apply(affine.apply(Vector::new(3.0, -4.0)));
+        assert!((point.x() - 3.0).abs() < 1e-3 && (point.y() + 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn singular_has_no_inverse() {
+        assert!(Affine::scale(0.0, 1.0).inverse().is_none());
+        assert!(Affine::scale(f32::INFINITY, 1.0).inverse().is_none());
+        assert!(Affine::scale(1e-30, 1e-30).inverse().is_none());
+    }
+}
@@ -1,24 +1,209 @@
+use std::f32::consts::TAU;
 use std::ops::{Deref, DerefMut, RangeInclusive};
 
-use self::color::Color;
+use self::color::{Blend, BlendMode, Color};
+use self::stroke::Stroke;
+use crate::util::affine::Affine;
 use crate::util::getter::Getter;
 use crate::util::vector::Vector;
 
+/// Frame-based animation of sprite sheets.
+pub mod animation;
+/// Aseprite file loading.
+#[cfg(feature = "aseprite")]
+pub mod aseprite;
+/// Sorted batch of images to be drawn in a single pass.
+pub mod batch;
+/// Camera looking at the world.
+pub mod camera;
 /// Image with dimensions unknown at compile-time.
 pub mod canvas;
 /// Color system used in `devotee`.
 pub mod color;
+/// Ordered dithering, fill patterns and error diffusion.
+pub mod dither;
+/// Image sliced into frames.
+pub mod sheet;
 /// Image with compile-time known dimensions.
 pub mod sprite;
+/// Outline drawing options.
+pub mod stroke;
+/// Tiled map loading.
+#[cfg(feature = "tiled")]
+pub mod tiled;
+/// Layered grid of tiles.
+pub mod tilemap;
+/// Rectangular regions of other images.
+pub mod view;
 
 /// Collection of drawing traits and functions compiles in a single prelude.
 pub mod prelude {
-    pub use super::color::Color;
-    pub use super::Image;
-    pub use super::{draw, mix, paint, printer, stamp};
+    pub use super::color::{Blend, BlendMode, Color};
+    pub use super::{blend, blend_image, draw, mix, paint, printer, stamp};
+    pub use super::{Connectivity, FillRule, Flip, Image, ImageMut};
     pub use super::{PaintTarget, Painter};
 }
 
+/// Image flipping option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Flip {
+    /// Keep the image as is.
+    #[default]
+    None,
+    /// Mirror the image left to right.
+    Horizontal,
+    /// Mirror the image top to bottom.
+    Vertical,
+    /// Mirror the image both left to right and top to bottom.
+    Both,
+}
+
+impl Flip {
+    /// Check if horizontal mirroring is applied.
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Flip::Horizontal | Flip::Both)
+    }
+
+    /// Check if vertical mirroring is applied.
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Flip::Vertical | Flip::Both)
+    }
+
+    fn apply(self, position: Vector<i32>, dimensions: Vector<i32>) -> Vector<i32> {
+        let x = if self.is_horizontal() {
+            dimensions.x() - 1 - position.x()
+        } else {
+            position.x()
+        };
+        let y = if self.is_vertical() {
+            dimensions.y() - 1 - position.y()
+        } else {
+            position.y()
+        };
+        Vector::new(x, y)
+    }
+}
+
+/// Clockwise image rotation by right angle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    /// Keep the image as is.
+    #[default]
+    None,
+    /// Rotate by 90 degrees clockwise.
+    Clockwise90,
+    /// Rotate by 180 degrees.
+    Clockwise180,
+    /// Rotate by 270 degrees clockwise.
+    Clockwise270,
+}
+
+impl Rotation {
+    /// Check if width and height are swapped by this rotation.
+    pub fn is_transposing(self) -> bool {
+        matches!(self, Rotation::Clockwise90 | Rotation::Clockwise270)
+    }
+
+    // Map rotated position back to the original one.
+    fn revert(self, position: Vector<i32>, dimensions: Vector<i32>) -> Vector<i32> {
+        let (x, y) = position.split();
+        let (width, height) = dimensions.split();
+        match self {
+            Rotation::None => position,
+            Rotation::Clockwise90 => Vector::new(y, height - 1 - x),
+            Rotation::Clockwise180 => Vector::new(width - 1 - x, height - 1 - y),
+            Rotation::Clockwise270 => Vector::new(width - 1 - y, x),
+        }
+    }
+}
+
+/// Pixel neighborhood used by flood fill.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// Pixels are connected by their sides.
+    #[default]
+    Four,
+    /// Pixels are connected by their sides and corners.
+    Eight,
+}
+
+/// Rule deciding which areas of self-intersecting or multi-contour polygon are inside.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FillRule {
+    /// Area is inside when a ray from it crosses odd count of edges.
+    #[default]
+    EvenOdd,
+    /// Area is inside when edges wind around it nonzero times.
+    NonZero,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+}
+
+/// Image blitting options.
+/// The source region gets flipped first, then rotated and finally scaled to the destination size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Blit {
+    source: Option<(Vector<i32>, Vector<i32>)>,
+    size: Option<Vector<i32>>,
+    scale: i32,
+    flip: Flip,
+    rotation: Rotation,
+}
+
+impl Blit {
+    /// Create new blit options to copy the whole image as is.
+    pub fn new() -> Self {
+        Self {
+            source: None,
+            size: None,
+            scale: 1,
+            flip: Flip::None,
+            rotation: Rotation::None,
+        }
+    }
+
+    /// Set source region of the image in `[from, to)` range.
+    pub fn with_source<I: Into<Vector<i32>>>(self, from: I, to: I) -> Self {
+        let source = Some((from.into(), to.into()));
+        Self { source, ..self }
+    }
+
+    /// Set destination size, the source is scaled with the nearest neighbour sampling.
+    pub fn with_size<I: Into<Vector<i32>>>(self, size: I) -> Self {
+        let size = Some(size.into());
+        Self { size, ..self }
+    }
+
+    /// Set integer scale factor.
+    /// It is ignored if the destination size is set explicitly.
+    pub fn with_scale(self, scale: i32) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Set flipping option.
+    pub fn with_flip(self, flip: Flip) -> Self {
+        Self { flip, ..self }
+    }
+
+    /// Set rotation option.
+    pub fn with_rotation(self, rotation: Rotation) -> Self {
+        Self { rotation, ..self }
+    }
+}
+
+impl Default for Blit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Mapper function accepts `x` and `y` coordinates and pixel value.
 pub type Mapper<P> = dyn FnMut(i32, i32, P) -> P;
 
@@ -40,6 +225,15 @@ where
     move |_, _, pixel| Color::mix(pixel, value.clone())
 }
 
+/// Helper blend function for pixel value combining.
+/// It blends provided `value` on top of original pixel value with the `mode`.
+pub fn blend<P>(value: P, mode: BlendMode) -> impl FnMut(i32, i32, P) -> P
+where
+    P: Clone + Blend,
+{
+    move |_, _, pixel| pixel.blend(value.clone(), mode)
+}
+
 /// Helper printer mapper for the `Text` trait.
 /// It breaks lines on newline symbol (`'\n'`) and ignores any special characters.
 pub fn printer<U>() -> impl FnMut(char, &U) -> Vector<i32>
@@ -75,6 +269,222 @@ pub fn stamp<P>() -> impl FnMut(i32, i32, P, i32, i32, P) -> P {
     move |_, _, _original, _, _, other| other
 }
 
+/// Helper blend mapper for image-to-image mapping.
+/// It blends pixels of the drawn image on top of the original with the `mode`.
+pub fn blend_image<P>(mode: BlendMode) -> impl FnMut(i32, i32, P, i32, i32, P) -> P
+where
+    P: Blend,
+{
+    move |_, _, original, _, _, other| original.blend(other, mode)
+}
+
+fn ellipse_quadrant(radii: Vector<i32>) -> Vec<Vector<i32>> {
+    let (a, b) = (radii.x().max(0) as i64, radii.y().max(0) as i64);
+    if a == 0 || b == 0 {
+        let points = (0..=b).map(|y| Vector::new(0, y as i32));
+        return points
+            .chain((1..=a).map(|x| Vector::new(x as i32, 0)))
+            .collect();
+    }
+
+    let (a2, b2) = (a * a, b * b);
+    let mut points = Vec::new();
+    let (mut x, mut y) = (0, b);
+    let (mut delta_x, mut delta_y) = (0, 2 * a2 * y);
+
+    // Decisions are scaled by 4 to keep them integer.
+    let mut decision = 4 * b2 - 4 * a2 * b + a2;
+    while delta_x < delta_y {
+        points.push(Vector::new(x as i32, y as i32));
+        x += 1;
+        delta_x += 2 * b2;
+        if decision < 0 {
+            decision += 4 * (delta_x + b2);
+        } else {
+            y -= 1;
+            delta_y -= 2 * a2;
+            decision += 4 * (delta_x - delta_y + b2);
+        }
+    }
+
+    let mut decision = b2 * (2 * x + 1) * (2 * x + 1) + 4 * a2 * (y - 1) * (y - 1) - 4 * a2 * b2;
+    while y >= 0 {
+        points.push(Vector::new(x as i32, y as i32));
+        y -= 1;
+        delta_y -= 2 * a2;
+        if decision > 0 {
+            decision += 4 * (a2 - delta_y);
+        } else {
+            x += 1;
+            delta_x += 2 * b2;
+            decision += 4 * (delta_x - delta_y + a2);
+        }
+    }
+    points
+}
+
+fn ellipse_spans(quadrant: &[Vector<i32>], height: i32) -> Vec<i32> {
+    let mut spans = vec![0; height.max(0) as usize + 1];
+    for point in quadrant {
+        let span = &mut spans[point.y() as usize];
+        *span = (*span).max(point.x());
+    }
+    spans
+}
+
+fn ellipse_outline(radii: Vector<i32>) -> Vec<Vector<i32>> {
+    let mut points: Vec<_> = ellipse_quadrant(radii)
+        .into_iter()
+        .flat_map(|point| {
+            let (x, y) = (point.x(), point.y());
+            [(x, y), (-x, y), (x, -y), (-x, -y)].map(Vector::from)
+        })
+        .collect();
+    points.sort();
+    points.dedup();
+    points
+}
+
+fn is_within_sweep(point: Vector<i32>, start: f32, end: f32) -> bool {
+    let sweep = end - start;
+    if sweep >= TAU || point == Vector::new(0, 0) {
+        return true;
+    }
+    let angle = (point.y() as f32).atan2(point.x() as f32);
+    (angle - start).rem_euclid(TAU) <= sweep.rem_euclid(TAU)
+}
+
+fn float(vector: Vector<i32>) -> Vector<f32> {
+    vector.map(|value| value as f32)
+}
+
+// Get pixels of the line, pixel `(x, y)` covers `[x, x + 1)` by `[y, y + 1)` area.
+fn line_points(from: Vector<f32>, to: Vector<f32>) -> Vec<Vector<i32>> {
+    let steep = (to.y() - from.y()).abs() > (to.x() - from.x()).abs();
+    let swap = |vector: Vector<f32>| {
+        if steep {
+            Vector::new(vector.y(), vector.x())
+        } else {
+            vector
+        }
+    };
+    let (from, to) = (swap(from), swap(to));
+    let delta = to - from;
+    let (start, end) = (from.x().floor() as i32, to.x().floor() as i32);
+    let step = if end >= start { 1 } else { -1 };
+    (0..=(end - start).abs())
+        .map(|index| {
+            let x = start + index * step;
+            // Sample at the pixel center, but never past the line ends.
+            let t = if delta.x() != 0.0 {
+                ((x as f32 + 0.5 - from.x()) / delta.x()).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let y = (from.y() + delta.y() * t).floor() as i32;
+            if steep {
+                Vector::new(y, x)
+            } else {
+                Vector::new(x, y)
+            }
+        })
+        .collect()
+}
+
+fn bezier_points(controls: &[Vector<f32>]) -> Vec<Vector<i32>> {
+    // Curve speed never exceeds the longest control edge times degree, so half pixel steps leave no gaps.
+    let degree = controls.len() - 1;
+    let longest = controls
+        .windows(2)
+        .map(|pair| {
+            let delta = pair[1] - pair[0];
+            delta.x().abs().max(delta.y().abs())
+        })
+        .fold(0.0, f32::max);
+    let steps = (longest * degree as f32 * 2.0).ceil().max(1.0) as usize;
+    let mut scratch = controls.to_vec();
+    (0..=steps)
+        .map(|step| {
+            let t = step as f32 / steps as f32;
+            scratch.copy_from_slice(controls);
+            for level in (1..=degree).rev() {
+                for index in 0..level {
+                    scratch[index] = scratch[index] + (scratch[index + 1] - scratch[index]) * t;
+                }
+            }
+            scratch[0].map(|value| (value + 0.5).floor() as i32)
+        })
+        .collect()
+}
+
+fn spline_controls(points: &[Vector<i32>], closed: bool) -> Vec<[Vector<f32>; 4]> {
+    let count = points.len() as i32;
+    let point = |index: i32| {
+        let index = if closed {
+            index.rem_euclid(count)
+        } else {
+            index.clamp(0, count - 1)
+        };
+        float(points[index as usize])
+    };
+    let segments = if closed { count } else { count - 1 };
+    (0..segments)
+        .map(|index| {
+            let (before, from, to, after) = (
+                point(index - 1),
+                point(index),
+                point(index + 1),
+                point(index + 2),
+            );
+            [
+                from,
+                from + (to - before) / 6.0,
+                to - (after - from) / 6.0,
+                to,
+            ]
+        })
+        .collect()
+}
+
+// Get path without repeated pixels and corner pixels of L-shaped steps.
+fn pixel_path<I: IntoIterator<Item = Vector<i32>>>(points: I, closed: bool) -> Vec<Vector<i32>> {
+    let is_diagonal =
+        |a: Vector<i32>, b: Vector<i32>| (a.x() - b.x()).abs() == 1 && (a.y() - b.y()).abs() == 1;
+    let mut path: Vec<Vector<i32>> = Vec::new();
+    for point in points {
+        if path.last() == Some(&point) {
+            continue;
+        }
+        if path.len() > 1 && is_diagonal(path[path.len() - 2], point) {
+            path.pop();
+        }
+        path.push(point);
+    }
+    if closed {
+        if path.len() > 1 && path.first() == path.last() {
+            path.pop();
+        }
+        if path.len() > 3 && is_diagonal(path[path.len() - 2], path[0]) {
+            path.pop();
+        }
+        if path.len() > 3 && is_diagonal(path[path.len() - 1], path[1]) {
+            path.remove(0);
+        }
+    }
+    path
+}
+
+// Get pixels of the line as drawn by `Painter::line`, in no particular order.
+fn line_scan_points(from: Vector<i32>, to: Vector<i32>) -> Vec<Vector<i32>> {
+    let (top, bottom) = (from.y().min(to.y()), from.y().max(to.y()));
+    if from.x() == to.x() {
+        return (top..=bottom).map(|y| Vector::new(from.x(), y)).collect();
+    }
+    (top..=bottom)
+        .flat_map(|y| line_scan(&from, &to, y).map(move |x| Vector::new(x, y)))
+        .collect()
+}
+
 fn line_scan(from: &Vector<i32>, to: &Vector<i32>, vertical_scan: i32) -> RangeInclusive<i32> {
     let (from, to) = if from.y() > to.y() {
         (from, to)
@@ -134,32 +544,19 @@ pub trait Image {
     type Pixel;
     /// Reference to pixel.
     type PixelRef<'a>
-    where
-        Self: 'a;
-    /// Mutable reference to pixel.
-    type PixelMut<'a>
     where
         Self: 'a;
     /// Get specific pixel reference.
     fn pixel(&self, position: Vector<i32>) -> Option<Self::PixelRef<'_>>;
-    /// Get specific pixel mutable reference.
-    fn pixel_mut(&mut self, position: Vector<i32>) -> Option<Self::PixelMut<'_>>;
     /// Get specific pixel reference without bounds check.
     ///
     /// # Safety
     /// - position must be in range [(0, 0), [width - 1, height - 1]]
     unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> Self::PixelRef<'_>;
-    /// Get specific pixel mutable reference without bounds check.
-    ///
-    /// # Safety
-    /// - position must be in range [(0, 0), [width - 1, height - 1]]
-    unsafe fn unsafe_pixel_mut(&mut self, position: Vector<i32>) -> Self::PixelMut<'_>;
     /// Get width of this image.
     fn width(&self) -> i32;
     /// Get height of this image.
     fn height(&self) -> i32;
-    /// Clear this image with color provided.
-    fn clear(&mut self, color: Self::Pixel);
 
     /// Get dimensions of this image.
     fn dimensions(&self) -> Vector<i32> {
@@ -167,14 +564,31 @@ pub trait Image {
     }
 }
 
+/// Image that can be modified and painted on.
+pub trait ImageMut: Image {
+    /// Mutable reference to pixel.
+    type PixelMut<'a>
+    where
+        Self: 'a;
+    /// Get specific pixel mutable reference.
+    fn pixel_mut(&mut self, position: Vector<i32>) -> Option<Self::PixelMut<'_>>;
+    /// Get specific pixel mutable reference without bounds check.
+    ///
+    /// # Safety
+    /// - position must be in range [(0, 0), [width - 1, height - 1]]
+    unsafe fn unsafe_pixel_mut(&mut self, position: Vector<i32>) -> Self::PixelMut<'_>;
+    /// Clear this image with color provided.
+    fn clear(&mut self, color: Self::Pixel);
+}
+
 /// Something that can be painted on.
 pub trait PaintTarget<T> {
     /// Get painter for painting.
-    fn painter(&mut self) -> Painter<T>;
+    fn painter(&mut self) -> Painter<'_, T>;
 }
 
 impl<T> PaintTarget<T> for T {
-    fn painter(&mut self) -> Painter<T> {
+    fn painter(&mut self) -> Painter<'_, T> {
         Painter::new(self)
     }
 }
@@ -183,6 +597,7 @@ impl<T> PaintTarget<T> for T {
 pub struct Painter<'a, I> {
     target: &'a mut I,
     offset: Vector<i32>,
+    clips: Vec<(Vector<i32>, Vector<i32>)>,
 }
 
 impl<'a, I> Painter<'a, I> {
@@ -190,6 +605,7 @@ impl<'a, I> Painter<'a, I> {
         Self {
             target,
             offset: Vector::new(0, 0),
+            clips: Vec::new(),
         }
     }
 
@@ -213,24 +629,45 @@ impl<'a, I> Painter<'a, I> {
     pub fn offset_mut(&mut self) -> &mut Vector<i32> {
         &mut self.offset
     }
+
+    /// Get new painter with desired clip rectangle pushed.
+    pub fn with_clip<V: Into<Vector<i32>>>(self, from: V, to: V) -> Self {
+        let mut result = self;
+        result.push_clip(from, to);
+        result
+    }
+
+    /// Restrict drawing to the rectangle in `[from, to)` range intersected with the current clip rectangle.
+    /// The rectangle is affected by the offset at the moment of the push.
+    pub fn push_clip<V: Into<Vector<i32>>>(&mut self, from: V, to: V) -> &mut Self {
+        let (from, to) = (from.into() + self.offset, to.into() + self.offset);
+        let (from, to) = match self.clips.last() {
+            Some((last_from, last_to)) => (
+                Vector::new(from.x().max(last_from.x()), from.y().max(last_from.y())),
+                Vector::new(to.x().min(last_to.x()), to.y().min(last_to.y())),
+            ),
+            None => (from, to),
+        };
+        self.clips.push((from, to));
+        self
+    }
+
+    /// Remove the last pushed clip rectangle and get it in target coordinates.
+    pub fn pop_clip(&mut self) -> Option<(Vector<i32>, Vector<i32>)> {
+        self.clips.pop()
+    }
 }
 
 impl<'a, T, P> Painter<'a, T>
 where
-    T: Image<Pixel = P>,
+    T: ImageMut<Pixel = P>,
     <T as Image>::Pixel: Clone,
     for<'b> <T as Image>::PixelRef<'b>: Deref<Target = <T as Image>::Pixel>,
-    for<'b> <T as Image>::PixelMut<'b>: DerefMut<Target = <T as Image>::Pixel>,
+    for<'b> <T as ImageMut>::PixelMut<'b>: DerefMut<Target = <T as Image>::Pixel>,
 {
-    fn map_on_pixel_offset<F: FnMut(i32, i32, P) -> P>(
-        &mut self,
-        point: Vector<i32>,
-        function: &mut F,
-    ) {
-        let point = point + self.offset;
-        if let Some(mut pixel) = self.target.pixel_mut(point) {
-            *pixel = function(point.x(), point.y(), pixel.clone());
-        }
+    fn is_clipped(&self, point: Vector<i32>) -> bool {
+        let (from, to) = self.clip();
+        point.x() < from.x() || point.y() < from.y() || point.x() >= to.x() || point.y() >= to.y()
     }
 
     fn map_on_pixel_raw<F: FnMut(i32, i32, P) -> P>(
@@ -238,6 +675,9 @@ where
         point: Vector<i32>,
         function: &mut F,
     ) {
+        if self.is_clipped(point) {
+            return;
+        }
         if let Some(mut pixel) = self.target.pixel_mut(point) {
             *pixel = function(point.x(), point.y(), pixel.clone());
         }
@@ -298,7 +738,8 @@ where
         function: &mut F,
         skip: usize,
     ) {
-        if x < 0 || x >= self.target.width() {
+        let (clip_from, clip_to) = self.clip();
+        if x < clip_from.x() || x >= clip_to.x() {
             return;
         }
 
@@ -325,7 +766,8 @@ where
         function: &mut F,
         skip: usize,
     ) {
-        if y < 0 || y >= self.target.height() {
+        let (clip_from, clip_to) = self.clip();
+        if y < clip_from.y() || y >= clip_to.y() {
             return;
         }
 
@@ -344,6 +786,20 @@ where
         }
     }
 
+    fn map_on_stroke_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        vertices: &[Vector<i32>],
+        closed: bool,
+        stroke: &Stroke,
+        function: &mut F,
+    ) {
+        let vertices: Vec<_> = vertices.iter().map(|v| *v + self.offset).collect();
+        let mask = stroke::polyline(stroke, &vertices, closed, self.clip());
+        for point in mask.positions() {
+            self.map_on_pixel_raw(point, function);
+        }
+    }
+
     fn map_on_filled_rect_offset<F: FnMut(i32, i32, P) -> P>(
         &mut self,
         from: Vector<i32>,
@@ -352,11 +808,12 @@ where
     ) {
         let from = from + self.offset;
         let to = to + self.offset;
+        let (clip_from, clip_to) = self.clip();
 
-        let start_x = from.x().max(0);
-        let start_y = from.y().max(0);
-        let end_x = (to.x()).min(self.target.width());
-        let end_y = (to.y()).min(self.target.height());
+        let start_x = from.x().max(clip_from.x());
+        let start_y = from.y().max(clip_from.y());
+        let end_x = to.x().min(clip_to.x());
+        let end_y = to.y().min(clip_to.y());
 
         for x in start_x..end_x {
             for y in start_y..end_y {
@@ -405,58 +862,221 @@ where
         }
     }
 
-    fn map_on_filled_sane_polygon_offset<F: FnMut(i32, i32, P) -> P>(
+    // Pixels listed in sorted `excluded` are skipped.
+    fn map_on_filled_contours_raw<F: FnMut(i32, i32, P) -> P>(
         &mut self,
-        vertices: &[Vector<i32>],
+        contours: &[Vec<Vector<f32>>],
+        rule: FillRule,
+        excluded: &[Vector<i32>],
         function: &mut F,
     ) {
-        // We do believe that there are at least 3 points in `vertices`.
-        let ((left, top), (right, bottom)) = vertices[1..].iter().fold(
-            (vertices[0].split(), vertices[0].split()),
-            |((left, top), (right, bottom)), value| {
-                let left = left.min(value.x());
-                let right = right.max(value.x());
-                let top = top.min(value.y());
-                let bottom = bottom.max(value.y());
-                ((left, top), (right, bottom))
-            },
-        );
-
-        let mut segments = vertices
-            .windows(2)
-            .map(|v| (v[0], v[1]))
-            .collect::<Vec<_>>();
-        segments.push((*vertices.last().unwrap(), vertices[0]));
-        for y in top..=bottom {
-            let mut segments = segments
-                .iter()
-                .filter(|(a, b)| (y >= a.y() && y <= b.y()) || (y >= b.y() && y <= a.y()))
-                .map(|(a, b)| (a, b, false, false, line_scan(a, b, y)))
-                .collect::<Vec<_>>();
+        // Edges are stored top to bottom along with their winding direction.
+        let mut edges = Vec::new();
+        for contour in contours.iter().filter(|contour| contour.len() > 2) {
+            let closing = (*contour.last().unwrap(), contour[0]);
+            for (a, b) in contour.windows(2).map(|v| (v[0], v[1])).chain([closing]) {
+                if a.y() < b.y() {
+                    edges.push((a, b, 1));
+                } else if a.y() > b.y() {
+                    edges.push((b, a, -1));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+        let top = edges.iter().map(|(a, _, _)| a.y()).fold(f32::MAX, f32::min);
+        let bottom = edges.iter().map(|(_, b, _)| b.y()).fold(f32::MIN, f32::max);
+
+        let (clip_from, clip_to) = self.clip();
+        let top = ((top - 0.5).ceil() as i32).max(clip_from.y());
+        let bottom = ((bottom - 0.5).ceil() as i32).min(clip_to.y());
+        let mut crossings = Vec::new();
+        for y in top..bottom {
+            let sample = y as f32 + 0.5;
+            crossings.clear();
+            for (a, b, winding) in edges.iter() {
+                if sample >= a.y() && sample < b.y() {
+                    let x = a.x() + (sample - a.y()) * (b.x() - a.x()) / (b.y() - a.y());
+                    crossings.push((x, *winding));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-            let mut counter = false;
-            for x in left..=right {
-                let mut should_paint = false;
-                let mut intersections = 0;
-                for (a, b, intersected, was_intersected, scan) in segments.iter_mut() {
-                    if x >= *scan.start() && x <= *scan.end() {
-                        should_paint = true;
-                        *intersected = true;
-                        if !*was_intersected && (y < a.y() || y < b.y()) {
-                            intersections += 1;
-                        }
-                    } else {
-                        *intersected = false;
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if !rule.is_inside(winding) {
+                    continue;
+                }
+                let left = ((pair[0].0 - 0.5).ceil() as i32).max(clip_from.x());
+                let right = ((pair[1].0 - 0.5).ceil() as i32).min(clip_to.x());
+                for x in left..right {
+                    let point = Vector::new(x, y);
+                    if excluded.binary_search(&point).is_err() {
+                        self.map_on_pixel_raw(point, function);
                     }
-                    *was_intersected = *intersected;
                 }
+            }
+        }
+    }
 
-                if intersections % 2 == 1 {
-                    counter = !counter;
-                }
+    // Vertices are on pixel centers, edges are the same as of `polygon`.
+    // Pixels on the edges are mapped once and excluded from the fill.
+    fn map_on_filled_polygons_raw<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        polygons: &[Vec<Vector<i32>>],
+        rule: FillRule,
+        function: &mut F,
+    ) {
+        let center = |point: Vector<i32>| float(point) + (0.5, 0.5);
+        let contours: Vec<Vec<Vector<f32>>> = polygons
+            .iter()
+            .map(|polygon| polygon.iter().copied().map(center).collect())
+            .collect();
+        let mut outline: Vec<Vector<i32>> = polygons
+            .iter()
+            .filter(|polygon| !polygon.is_empty())
+            .flat_map(|polygon| {
+                let closing = (*polygon.last().unwrap(), polygon[0]);
+                polygon
+                    .windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .chain([closing])
+                    .flat_map(|(from, to)| line_scan_points(from, to))
+            })
+            .collect();
+        outline.sort();
+        outline.dedup();
+        self.map_on_filled_contours_raw(&contours, rule, &outline, function);
+        for point in outline {
+            self.map_on_pixel_raw(point, function);
+        }
+    }
 
-                if should_paint || counter {
-                    self.map_on_pixel_offset((x, y).into(), function);
+    fn map_on_path_raw<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        path: &[Vector<i32>],
+        filled: bool,
+        function: &mut F,
+    ) {
+        if filled {
+            let contour = path
+                .iter()
+                .map(|point| float(*point) + (0.5, 0.5))
+                .collect();
+            let mut excluded = path.to_vec();
+            excluded.sort();
+            self.map_on_filled_contours_raw(&[contour], FillRule::NonZero, &excluded, function);
+        }
+        for point in path {
+            self.map_on_pixel_raw(*point, function);
+        }
+    }
+
+    fn map_on_bezier_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        controls: &[Vector<i32>],
+        filled: bool,
+        function: &mut F,
+    ) {
+        let controls: Vec<_> = controls
+            .iter()
+            .map(|point| float(*point + self.offset))
+            .collect();
+        let mut points = bezier_points(&controls);
+        if filled {
+            let (from, to) = (controls[controls.len() - 1], controls[0]);
+            points.extend(bezier_points(&[from, to]));
+        }
+        let path = pixel_path(points, filled);
+        self.map_on_path_raw(&path, filled, function);
+    }
+
+    fn map_on_spline_offset<I, F>(
+        &mut self,
+        points: &[I],
+        closed: bool,
+        filled: bool,
+        function: &mut F,
+    ) where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let points: Vec<Vector<i32>> = points
+            .iter()
+            .cloned()
+            .map(|point| point.into() + self.offset)
+            .collect();
+        let path = match points.len() {
+            0 => return,
+            1 => points,
+            _ => {
+                let segments = spline_controls(&points, closed);
+                pixel_path(
+                    segments.iter().flat_map(|segment| bezier_points(segment)),
+                    closed,
+                )
+            }
+        };
+        self.map_on_path_raw(&path, filled, function);
+    }
+
+    fn map_on_polygon_sub_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        vertices: &[Vector<f32>],
+        filled: bool,
+        function: &mut F,
+    ) {
+        let offset = float(self.offset);
+        let vertices: Vec<_> = vertices.iter().map(|vertex| *vertex + offset).collect();
+        if filled {
+            self.map_on_filled_contours_raw(&[vertices], FillRule::default(), &[], function);
+            return;
+        }
+        let closing = (*vertices.last().unwrap(), vertices[0]);
+        let points = vertices
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .chain([closing])
+            .flat_map(|(from, to)| line_points(from, to));
+        let path = pixel_path(points, true);
+        self.map_on_path_raw(&path, false, function);
+    }
+
+    fn map_on_circle_sub_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        center: Vector<f32>,
+        radius: f32,
+        filled: bool,
+        function: &mut F,
+    ) {
+        let center = center + float(self.offset);
+        let radius = radius.max(0.0);
+        let top = (center.y() - radius - 0.5).ceil() as i32;
+        let bottom = (center.y() + radius - 0.5).floor() as i32;
+        // Pixel is inside when its center is within the radius.
+        let spans: Vec<_> = (top..=bottom)
+            .map(|y| {
+                let distance = y as f32 + 0.5 - center.y();
+                let half = (radius * radius - distance * distance).max(0.0).sqrt();
+                let left = (center.x() - half - 0.5).ceil() as i32;
+                let right = (center.x() + half - 0.5).floor() as i32;
+                (left, right)
+            })
+            .collect();
+        let is_inside = |row: i32, x: i32| {
+            let index = row - top;
+            index >= 0
+                && (index as usize) < spans.len()
+                && (spans[index as usize].0..=spans[index as usize].1).contains(&x)
+        };
+        for (y, (left, right)) in (top..=bottom).zip(spans.iter().copied()) {
+            for x in left..=right {
+                let is_edge =
+                    x == left || x == right || !is_inside(y - 1, x) || !is_inside(y + 1, x);
+                if filled || is_edge {
+                    self.map_on_pixel_raw((x, y).into(), function);
                 }
             }
         }
@@ -576,6 +1196,45 @@ where
         }
     }
 
+    fn map_on_filled_ellipse_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        center: Vector<i32>,
+        radii: Vector<i32>,
+        function: &mut F,
+    ) {
+        let center = center + self.offset;
+        let spans = ellipse_spans(&ellipse_quadrant(radii), radii.y());
+        for (y, span) in spans.into_iter().enumerate() {
+            let (y, left, right) = (y as i32, center.x() - span, center.x() + span);
+            self.map_horizontal_line_raw(left, right, center.y() + y, function, 0);
+            if y != 0 {
+                self.map_horizontal_line_raw(left, right, center.y() - y, function, 0);
+            }
+        }
+    }
+
+    fn map_on_filled_rounded_rect_offset<F: FnMut(i32, i32, P) -> P>(
+        &mut self,
+        from: Vector<i32>,
+        to: Vector<i32>,
+        radius: i32,
+        function: &mut F,
+    ) {
+        let (from, to) = (from + self.offset, to + self.offset - (1, 1));
+        if to.x() < from.x() || to.y() < from.y() {
+            return;
+        }
+        let radius = radius.clamp(0, (to.x() - from.x()).min(to.y() - from.y()) / 2);
+        let spans = ellipse_spans(&ellipse_quadrant((radius, radius).into()), radius);
+        let (top, bottom) = (from.y() + radius, to.y() - radius);
+        let (left, right) = (from.x() + radius, to.x() - radius);
+        for y in from.y()..=to.y() {
+            let distance = (top - y).max(y - bottom).max(0);
+            let span = spans[distance as usize];
+            self.map_horizontal_line_raw(left - span, right + span, y, function, 0);
+        }
+    }
+
     fn zip_map_images_offset<
         'b,
         O: Clone,
@@ -585,36 +1244,159 @@ where
         &mut self,
         at: Vector<i32>,
         image: &'b U,
+        flip: Flip,
         function: &mut F,
     ) where
         <U as Image>::PixelRef<'b>: Deref<Target = O>,
     {
         let at = at + self.offset;
-        let image_start_x = if at.x() < 0 { -at.x() } else { 0 };
-        let image_start_y = if at.y() < 0 { -at.y() } else { 0 };
-
-        let image_end_x = if at.x() + image.width() >= self.target.width() {
-            self.target.width() - at.x()
-        } else {
-            image.width()
-        };
-        let image_end_y = if at.y() + image.height() >= self.target.height() {
-            self.target.height() - at.y()
-        } else {
-            image.height()
-        };
+        let dimensions = image.dimensions();
+        let (clip_from, clip_to) = self.clip();
+        let image_start_x = (clip_from.x() - at.x()).max(0);
+        let image_start_y = (clip_from.y() - at.y()).max(0);
+        let image_end_x = image.width().min(clip_to.x() - at.x());
+        let image_end_y = image.height().min(clip_to.y() - at.y());
         for x in image_start_x..image_end_x {
             for y in image_start_y..image_end_y {
                 let step = (x, y).into();
                 let pose = at + step;
+                let source = flip.apply(step, dimensions);
                 unsafe {
-                    let color = Image::unsafe_pixel(image, step);
+                    let color = Image::unsafe_pixel(image, source);
                     let pixel = function(
                         pose.x(),
                         pose.y(),
                         self.target.unsafe_pixel(pose).clone(),
+                        source.x(),
+                        source.y(),
+                        color.clone(),
+                    );
+                    *self.target.unsafe_pixel_mut(pose) = pixel;
+                }
+            }
+        }
+    }
+
+    fn zip_map_images_blit_offset<
+        'b,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        U: 'b + Image<Pixel = O> + ?Sized,
+    >(
+        &mut self,
+        at: Vector<i32>,
+        image: &'b U,
+        blit: Blit,
+        function: &mut F,
+    ) where
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let (origin, end) = blit
+            .source
+            .map(|(from, to)| {
+                (
+                    Vector::new(from.x().max(0), from.y().max(0)),
+                    Vector::new(to.x().min(image.width()), to.y().min(image.height())),
+                )
+            })
+            .unwrap_or((Vector::new(0, 0), image.dimensions()));
+        let region = end - origin;
+        if region.x() <= 0 || region.y() <= 0 {
+            return;
+        }
+        let oriented = if blit.rotation.is_transposing() {
+            Vector::new(region.y(), region.x())
+        } else {
+            region
+        };
+        let size = blit.size.unwrap_or(oriented * blit.scale);
+        if size.x() <= 0 || size.y() <= 0 {
+            return;
+        }
+
+        let at = at + self.offset;
+        let (clip_from, clip_to) = self.clip();
+        let start_x = (clip_from.x() - at.x()).max(0);
+        let start_y = (clip_from.y() - at.y()).max(0);
+        let end_x = size.x().min(clip_to.x() - at.x());
+        let end_y = size.y().min(clip_to.y() - at.y());
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                let scaled = Vector::new(x * oriented.x() / size.x(), y * oriented.y() / size.y());
+                let flipped = blit.rotation.revert(scaled, region);
+                let source = blit.flip.apply(flipped, region) + origin;
+                let pose = at + (x, y);
+                unsafe {
+                    let color = Image::unsafe_pixel(image, source);
+                    let pixel = function(
+                        pose.x(),
+                        pose.y(),
+                        self.target.unsafe_pixel(pose).clone(),
+                        source.x(),
+                        source.y(),
+                        color.clone(),
+                    );
+                    *self.target.unsafe_pixel_mut(pose) = pixel;
+                }
+            }
+        }
+    }
+
+    fn zip_map_images_affine_offset<
+        'b,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        U: 'b + Image<Pixel = O> + ?Sized,
+    >(
+        &mut self,
+        at: Vector<i32>,
+        image: &'b U,
+        transform: Affine,
+        function: &mut F,
+    ) where
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let Some(inverse) = transform.inverse() else {
+            return;
+        };
+        let at = at + self.offset;
+        let (width, height) = (image.width() as f32, image.height() as f32);
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .map(|corner| transform.apply(corner.into()));
+        let (left, top, right, bottom) = corners.iter().fold(
+            (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |(left, top, right, bottom), corner| {
+                (
+                    left.min(corner.x()),
+                    top.min(corner.y()),
+                    right.max(corner.x()),
+                    bottom.max(corner.y()),
+                )
+            },
+        );
+
+        let (clip_from, clip_to) = self.clip();
+        let start_x = (left.floor() as i32 + at.x()).max(clip_from.x());
+        let start_y = (top.floor() as i32 + at.y()).max(clip_from.y());
+        let end_x = (right.ceil() as i32 + at.x()).min(clip_to.x());
+        let end_y = (bottom.ceil() as i32 + at.y()).min(clip_to.y());
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                let center = Vector::new((x - at.x()) as f32 + 0.5, (y - at.y()) as f32 + 0.5);
+                let local = inverse.apply(center);
+                if local.x() < 0.0 || local.y() < 0.0 || local.x() >= width || local.y() >= height {
+                    continue;
+                }
+                let source = Vector::new(local.x() as i32, local.y() as i32);
+                let pose = Vector::new(x, y);
+                unsafe {
+                    let color = Image::unsafe_pixel(image, source);
+                    let pixel = function(
                         x,
                         y,
+                        self.target.unsafe_pixel(pose).clone(),
+                        source.x(),
+                        source.y(),
                         color.clone(),
                     );
                     *self.target.unsafe_pixel_mut(pose) = pixel;
@@ -633,9 +1415,25 @@ where
         Image::height(self.target)
     }
 
+    /// Get current clip rectangle in target coordinates in `[from, to)` range.
+    /// Without pushed clip rectangles it covers the whole target.
+    pub fn clip(&self) -> (Vector<i32>, Vector<i32>) {
+        let (width, height) = (self.target.width(), self.target.height());
+        let (from, to) = self
+            .clips
+            .last()
+            .copied()
+            .unwrap_or((Vector::new(0, 0), Vector::new(width, height)));
+        (
+            Vector::new(from.x().max(0), from.y().max(0)),
+            Vector::new(to.x().min(width), to.y().min(height)),
+        )
+    }
+
     /// Clear the target with provided color.
+    /// The clip rectangle is ignored.
     pub fn clear(&mut self, clear_color: P) {
-        Image::clear(self.target, clear_color)
+        ImageMut::clear(self.target, clear_color)
     }
 
     /// Get reference to pixel.
@@ -651,7 +1449,7 @@ where
     where
         I: Into<Vector<i32>>,
     {
-        Image::pixel_mut(self.target, position.into() + self.offset)
+        ImageMut::pixel_mut(self.target, position.into() + self.offset)
     }
 
     /// Use provided function on a pixel at given position.
@@ -662,6 +1460,9 @@ where
     {
         let mut function = function;
         let position = position.into();
+        if self.is_clipped(position + self.offset) {
+            return;
+        }
         if let Some(mut pixel) = self.pixel_mut(position) {
             *pixel = function(position.x(), position.y(), pixel.clone());
         }
@@ -728,6 +1529,8 @@ where
     }
 
     /// Use provided function on each pixel in polygon.
+    /// Vertices are on pixel centers, edges are the same as drawn by `polygon`.
+    /// Self-intersections are resolved by the even-odd rule, same as `contours_f` with a single contour.
     pub fn polygon_f<I, F>(&mut self, vertices: &[I], function: F)
     where
         I: Into<Vector<i32>> + Clone,
@@ -739,10 +1542,35 @@ where
             0 => (),
             1 => self.mod_pixel(vertices[0], function),
             2 => self.line(vertices[0], vertices[1], function),
-            _ => self.map_on_filled_sane_polygon_offset(&vertices, &mut function),
+            _ => {
+                let vertices = vertices.into_iter().map(|vertex| vertex + self.offset);
+                let polygons = [vertices.collect()];
+                self.map_on_filled_polygons_raw(&polygons, FillRule::EvenOdd, &mut function);
+            }
         }
     }
 
+    /// Use provided function on each pixel inside the shape made of closed `contours`.
+    /// Contours may be concave and self-intersecting, overlaps and holes are resolved by `rule`.
+    /// Vertices are on pixel centers, contours are included and drawn as by `polygon`.
+    pub fn contours_f<I, C, F>(&mut self, contours: &[C], rule: FillRule, function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        C: AsRef<[I]>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let offset = self.offset;
+        let polygons: Vec<Vec<Vector<i32>>> = contours
+            .iter()
+            .map(|contour| {
+                let contour = contour.as_ref().iter().cloned();
+                contour.map(|point| point.into() + offset).collect()
+            })
+            .collect();
+        let mut function = function;
+        self.map_on_filled_polygons_raw(&polygons, rule, &mut function);
+    }
+
     /// Use provided function on each pixel of polygon bounds.
     pub fn polygon<I, F>(&mut self, vertices: &[I], function: F)
     where
@@ -795,6 +1623,465 @@ where
         self.map_on_circle_offset(center, radius, &mut function);
     }
 
+    /// Use provided function on each pixel in ellipse with horizontal and vertical `radii`.
+    pub fn ellipse_f<I, F>(&mut self, center: I, radii: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let (center, radii) = (center.into(), radii.into());
+        let mut function = function;
+        self.map_on_filled_ellipse_offset(center, radii, &mut function);
+    }
+
+    /// Use provided function on each pixel of ellipse bounds with horizontal and vertical `radii`.
+    pub fn ellipse<I, F>(&mut self, center: I, radii: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let center = center.into() + self.offset;
+        let mut function = function;
+        for point in ellipse_outline(radii.into()) {
+            self.map_on_pixel_raw(center + point, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel of circle arc.
+    /// Angles are in radians, going clockwise from the positive x axis.
+    pub fn arc<I, F>(&mut self, center: I, radius: i32, start: f32, end: f32, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let center = center.into() + self.offset;
+        let mut function = function;
+        for point in ellipse_outline((radius, radius).into()) {
+            if is_within_sweep(point, start, end) {
+                self.map_on_pixel_raw(center + point, &mut function);
+            }
+        }
+    }
+
+    /// Use provided function on each pixel in circle sector.
+    /// Angles are in radians, going clockwise from the positive x axis.
+    pub fn pie_f<I, F>(&mut self, center: I, radius: i32, start: f32, end: f32, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let center = center.into() + self.offset;
+        let mut function = function;
+        let spans = ellipse_spans(&ellipse_quadrant((radius, radius).into()), radius);
+        for (y, span) in spans.into_iter().enumerate() {
+            let y = y as i32;
+            let rows: &[i32] = if y == 0 { &[0] } else { &[y, -y] };
+            for (x, y) in rows
+                .iter()
+                .flat_map(|y| (-span..=span).map(move |x| (x, *y)))
+            {
+                let point = Vector::new(x, y);
+                if is_within_sweep(point, start, end) {
+                    self.map_on_pixel_raw(center + point, &mut function);
+                }
+            }
+        }
+    }
+
+    /// Use provided function on each pixel of circle sector bounds.
+    /// Angles are in radians, going clockwise from the positive x axis.
+    pub fn pie<I, F>(&mut self, center: I, radius: i32, start: f32, end: f32, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let center = center.into();
+        let mut function = function;
+        if end - start >= TAU {
+            self.arc(center, radius, start, end, function);
+            return;
+        }
+        let edge = |angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            Vector::new(
+                (cos * radius as f32).round() as i32,
+                (sin * radius as f32).round() as i32,
+            )
+        };
+        let center = center + self.offset;
+        let origin = float(center) + (0.5, 0.5);
+        let mut points: Vec<_> = ellipse_outline((radius, radius).into())
+            .into_iter()
+            .filter(|point| is_within_sweep(*point, start, end))
+            .map(|point| center + point)
+            .collect();
+        for angle in [start, end] {
+            points.extend(line_points(origin, origin + float(edge(angle))));
+        }
+        points.sort();
+        points.dedup();
+
+        for point in points {
+            self.map_on_pixel_raw(point, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel in a rectangle with rounded corners of `radius`.
+    pub fn rect_rounded_f<I, F>(&mut self, from: I, to: I, radius: i32, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let (from, to) = (from.into(), to.into());
+        let mut function = function;
+        self.map_on_filled_rounded_rect_offset(from, to, radius, &mut function);
+    }
+
+    /// Use provided function on each pixel of rectangle with rounded corners of `radius` bounds.
+    pub fn rect_rounded<I, F>(&mut self, from: I, to: I, radius: i32, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let (from, to) = (from.into() + self.offset, to.into() - (1, 1) + self.offset);
+        if to.x() < from.x() || to.y() < from.y() {
+            return;
+        }
+        let radius = radius.clamp(0, (to.x() - from.x()).min(to.y() - from.y()) / 2);
+        let (top_left, bottom_right) = (from + (radius, radius), to - (radius, radius));
+
+        let mut points = Vec::new();
+        for point in ellipse_quadrant((radius, radius).into()) {
+            let (x, y) = (point.x(), point.y());
+            points.push(bottom_right + (x, y));
+            points.push(Vector::new(top_left.x() - x, bottom_right.y() + y));
+            points.push(Vector::new(bottom_right.x() + x, top_left.y() - y));
+            points.push(top_left - (x, y));
+        }
+        for x in top_left.x() + 1..bottom_right.x() {
+            points.push(Vector::new(x, from.y()));
+            points.push(Vector::new(x, to.y()));
+        }
+        for y in top_left.y() + 1..bottom_right.y() {
+            points.push(Vector::new(from.x(), y));
+            points.push(Vector::new(to.x(), y));
+        }
+        points.sort();
+        points.dedup();
+
+        let mut function = function;
+        for point in points {
+            self.map_on_pixel_raw(point, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel of the region connected to `start` where `predicate` holds.
+    /// The `predicate` is checked against original pixel values, so excluding border pixels gives boundary fill.
+    /// The region is limited by the clip rectangle.
+    pub fn flood_fill<I, M, F>(
+        &mut self,
+        start: I,
+        connectivity: Connectivity,
+        predicate: M,
+        function: F,
+    ) where
+        I: Into<Vector<i32>>,
+        M: FnMut(&P) -> bool,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let start = start.into() + self.offset;
+        let (from, to) = self.clip();
+        if self.is_clipped(start) {
+            return;
+        }
+        let width = to.x() - from.x();
+        let mut visited = vec![false; (width * (to.y() - from.y())) as usize];
+        let index = |x: i32, y: i32| (x - from.x() + (y - from.y()) * width) as usize;
+
+        let mut predicate = predicate;
+        let mut function = function;
+        let mut is_fillable = |target: &T, visited: &[bool], x: i32, y: i32| {
+            !visited[index(x, y)]
+                && target
+                    .pixel((x, y).into())
+                    .is_some_and(|pixel| predicate(&pixel))
+        };
+        let spread = match connectivity {
+            Connectivity::Four => 0,
+            Connectivity::Eight => 1,
+        };
+
+        let mut seeds = vec![start];
+        while let Some(seed) = seeds.pop() {
+            let (x, y) = seed.split();
+            if !is_fillable(self.target, &visited, x, y) {
+                continue;
+            }
+            let mut left = x;
+            while left > from.x() && is_fillable(self.target, &visited, left - 1, y) {
+                left -= 1;
+            }
+            let mut right = x;
+            while right + 1 < to.x() && is_fillable(self.target, &visited, right + 1, y) {
+                right += 1;
+            }
+            for x in left..=right {
+                visited[index(x, y)] = true;
+                self.map_on_pixel_raw((x, y).into(), &mut function);
+            }
+
+            let (scan_left, scan_right) = (
+                (left - spread).max(from.x()),
+                (right + spread).min(to.x() - 1),
+            );
+            for y in [y - 1, y + 1] {
+                if y < from.y() || y >= to.y() {
+                    continue;
+                }
+                let mut in_run = false;
+                for x in scan_left..=scan_right {
+                    let fillable = is_fillable(self.target, &visited, x, y);
+                    if fillable && !in_run {
+                        seeds.push((x, y).into());
+                    }
+                    in_run = fillable;
+                }
+            }
+        }
+    }
+
+    /// Use provided function on each pixel of quadratic Bezier curve.
+    pub fn bezier_quadratic<I, F>(&mut self, from: I, control: I, to: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let controls = [from.into(), control.into(), to.into()];
+        let mut function = function;
+        self.map_on_bezier_offset(&controls, false, &mut function);
+    }
+
+    /// Use provided function on each pixel in the shape bounded by quadratic Bezier curve and its chord.
+    pub fn bezier_quadratic_f<I, F>(&mut self, from: I, control: I, to: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let controls = [from.into(), control.into(), to.into()];
+        let mut function = function;
+        self.map_on_bezier_offset(&controls, true, &mut function);
+    }
+
+    /// Use provided function on each pixel of cubic Bezier curve.
+    pub fn bezier_cubic<I, F>(&mut self, from: I, first: I, second: I, to: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let controls = [from.into(), first.into(), second.into(), to.into()];
+        let mut function = function;
+        self.map_on_bezier_offset(&controls, false, &mut function);
+    }
+
+    /// Use provided function on each pixel in the shape bounded by cubic Bezier curve and its chord.
+    pub fn bezier_cubic_f<I, F>(&mut self, from: I, first: I, second: I, to: I, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let controls = [from.into(), first.into(), second.into(), to.into()];
+        let mut function = function;
+        self.map_on_bezier_offset(&controls, true, &mut function);
+    }
+
+    /// Use provided function on each pixel of Catmull-Rom spline passing through `points`.
+    pub fn spline<I, F>(&mut self, points: &[I], function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let mut function = function;
+        self.map_on_spline_offset(points, false, false, &mut function);
+    }
+
+    /// Use provided function on each pixel of closed Catmull-Rom spline passing through `points`.
+    pub fn spline_closed<I, F>(&mut self, points: &[I], function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let mut function = function;
+        self.map_on_spline_offset(points, true, false, &mut function);
+    }
+
+    /// Use provided function on each pixel in closed Catmull-Rom spline passing through `points`.
+    pub fn spline_f<I, F>(&mut self, points: &[I], function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let mut function = function;
+        self.map_on_spline_offset(points, true, true, &mut function);
+    }
+
+    /// Use provided function on each pixel of a line between fractional positions.
+    /// Pixel `(x, y)` covers `[x, x + 1)` by `[y, y + 1)` area.
+    pub fn line_sub<I, F>(&mut self, from: I, to: I, function: F)
+    where
+        I: Into<Vector<f32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let offset = float(self.offset);
+        let (from, to) = (from.into() + offset, to.into() + offset);
+        let mut function = function;
+        for point in line_points(from, to) {
+            self.map_on_pixel_raw(point, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel with center in triangle with fractional vertices.
+    pub fn triangle_f_sub<I, F>(&mut self, vertices: [I; 3], function: F)
+    where
+        I: Into<Vector<f32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices = vertices.map(Into::into);
+        let mut function = function;
+        self.map_on_polygon_sub_offset(&vertices, true, &mut function);
+    }
+
+    /// Use provided function on each pixel of triangle with fractional vertices bounds.
+    pub fn triangle_sub<I, F>(&mut self, vertices: [I; 3], function: F)
+    where
+        I: Into<Vector<f32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices = vertices.map(Into::into);
+        let mut function = function;
+        self.map_on_polygon_sub_offset(&vertices, false, &mut function);
+    }
+
+    /// Use provided function on each pixel with center in polygon with fractional vertices.
+    pub fn polygon_f_sub<I, F>(&mut self, vertices: &[I], function: F)
+    where
+        I: Into<Vector<f32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices: Vec<Vector<f32>> = vertices.iter().cloned().map(Into::into).collect();
+        let mut function = function;
+        if !vertices.is_empty() {
+            self.map_on_polygon_sub_offset(&vertices, true, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel of polygon with fractional vertices bounds.
+    pub fn polygon_sub<I, F>(&mut self, vertices: &[I], function: F)
+    where
+        I: Into<Vector<f32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices: Vec<Vector<f32>> = vertices.iter().cloned().map(Into::into).collect();
+        let mut function = function;
+        if !vertices.is_empty() {
+            self.map_on_polygon_sub_offset(&vertices, false, &mut function);
+        }
+    }
+
+    /// Use provided function on each pixel with center in circle with fractional center and radius.
+    pub fn circle_f_sub<I, F>(&mut self, center: I, radius: f32, function: F)
+    where
+        I: Into<Vector<f32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let mut function = function;
+        self.map_on_circle_sub_offset(center.into(), radius, true, &mut function);
+    }
+
+    /// Use provided function on each pixel of circle with fractional center and radius bounds.
+    pub fn circle_sub<I, F>(&mut self, center: I, radius: f32, function: F)
+    where
+        I: Into<Vector<f32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let mut function = function;
+        self.map_on_circle_sub_offset(center.into(), radius, false, &mut function);
+    }
+
+    /// Use provided function on each pixel of a line drawn with `stroke`.
+    pub fn line_stroked<I, F>(&mut self, from: I, to: I, stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices = [from.into(), to.into()];
+        let mut function = function;
+        self.map_on_stroke_offset(&vertices, false, stroke, &mut function);
+    }
+
+    /// Use provided function on each pixel of connected lines drawn with `stroke`.
+    pub fn polyline_stroked<I, F>(&mut self, vertices: &[I], stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices: Vec<Vector<i32>> = vertices.iter().cloned().map(Into::into).collect();
+        let mut function = function;
+        self.map_on_stroke_offset(&vertices, false, stroke, &mut function);
+    }
+
+    /// Use provided function on each pixel of rectangle bounds drawn with `stroke`.
+    pub fn rect_stroked<I, F>(&mut self, from: I, to: I, stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let (from, to) = (from.into(), to.into() - (1, 1));
+        let vertices = [
+            from,
+            (to.x(), from.y()).into(),
+            to,
+            (from.x(), to.y()).into(),
+        ];
+        let mut function = function;
+        self.map_on_stroke_offset(&vertices, true, stroke, &mut function);
+    }
+
+    /// Use provided function on each pixel of triangle bounds drawn with `stroke`.
+    pub fn triangle_stroked<I, F>(&mut self, vertices: [I; 3], stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices = vertices.map(Into::into);
+        let mut function = function;
+        self.map_on_stroke_offset(&vertices, true, stroke, &mut function);
+    }
+
+    /// Use provided function on each pixel of polygon bounds drawn with `stroke`.
+    pub fn polygon_stroked<I, F>(&mut self, vertices: &[I], stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>> + Clone,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let vertices: Vec<Vector<i32>> = vertices.iter().cloned().map(Into::into).collect();
+        let mut function = function;
+        self.map_on_stroke_offset(&vertices, true, stroke, &mut function);
+    }
+
+    /// Use provided function on each pixel of circle bounds drawn with `stroke`.
+    pub fn circle_stroked<I, F>(&mut self, center: I, radius: i32, stroke: &Stroke, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: FnMut(i32, i32, P) -> P,
+    {
+        let center = center.into() + self.offset;
+        let mut function = function;
+        let mask = stroke::circle(stroke, center, radius, self.clip());
+        for point in mask.positions() {
+            self.map_on_pixel_raw(point, &mut function);
+        }
+    }
+
     /// Get reference to pixel.
     ///
     /// # Safety
@@ -814,7 +2101,7 @@ where
     where
         I: Into<Vector<i32>>,
     {
-        Image::unsafe_pixel_mut(self.target, position.into() + self.offset)
+        ImageMut::unsafe_pixel_mut(self.target, position.into() + self.offset)
     }
 
     /// Use provided function and given image on this drawable.
@@ -828,7 +2115,91 @@ where
     {
         let at = at.into();
         let mut function = function;
-        self.zip_map_images_offset(at, image, &mut function)
+        self.zip_map_images_offset(at, image, Flip::None, &mut function)
+    }
+
+    /// Use provided function and given flipped image on this drawable.
+    pub fn image_flipped<'b, I, F, O, U>(&mut self, at: I, image: &'b U, flip: Flip, function: F)
+    where
+        I: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O> + ?Sized,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let at = at.into();
+        let mut function = function;
+        self.zip_map_images_offset(at, image, flip, &mut function)
+    }
+
+    /// Use provided function and given image rotated clockwise on this drawable.
+    pub fn image_rotated<'b, I, F, O, U>(
+        &mut self,
+        at: I,
+        image: &'b U,
+        rotation: Rotation,
+        function: F,
+    ) where
+        I: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O> + ?Sized,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let at = at.into();
+        let mut function = function;
+        let blit = Blit::new().with_rotation(rotation);
+        self.zip_map_images_blit_offset(at, image, blit, &mut function)
+    }
+
+    /// Use provided function and given image scaled to the `[from, to)` rectangle on this drawable.
+    pub fn image_scaled<'b, I, F, O, U>(&mut self, from: I, to: I, image: &'b U, function: F)
+    where
+        I: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O> + ?Sized,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let (from, to) = (from.into(), to.into());
+        let mut function = function;
+        let blit = Blit::new().with_size(to - from);
+        self.zip_map_images_blit_offset(from, image, blit, &mut function)
+    }
+
+    /// Use provided function and given image transformed by blit options on this drawable.
+    pub fn blit<'b, I, F, O, U>(&mut self, at: I, image: &'b U, blit: Blit, function: F)
+    where
+        I: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O> + ?Sized,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let at = at.into();
+        let mut function = function;
+        self.zip_map_images_blit_offset(at, image, blit, &mut function)
+    }
+
+    /// Use provided function and given image transformed by affine `transform` on this drawable.
+    /// The `transform` maps image coordinates into coordinates relative to `at` point.
+    /// Pixels are sampled with the nearest neighbour approach.
+    pub fn image_affine<'b, I, F, O, U>(
+        &mut self,
+        at: I,
+        image: &'b U,
+        transform: Affine,
+        function: F,
+    ) where
+        I: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O> + ?Sized,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        <U as Image>::PixelRef<'b>: Deref<Target = O>,
+    {
+        let at = at.into();
+        let mut function = function;
+        self.zip_map_images_affine_offset(at, image, transform, &mut function)
     }
 
     /// Use provided spatial mapper, font and mapper function to draw text.
@@ -853,7 +2224,7 @@ where
         for code_point in text.chars() {
             if let Some(symbol) = font.get(&code_point) {
                 let local = at + mapper(code_point, symbol);
-                self.zip_map_images_offset(local, symbol, &mut function);
+                self.zip_map_images_offset(local, symbol, Flip::None, &mut function);
             }
         }
     }
@@ -875,3 +2246,120 @@ impl<'a, P: 'a, I: PixelsIterator<'a, P> + ?Sized> PixelsIterator<'a, P> for Box
         I::pixels(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::canvas::Canvas;
+    use super::*;
+
+    // Filler used by `polygon_f` before the general fill, kept to check the output did not change.
+    fn sane_polygon(vertices: &[Vector<i32>]) -> Vec<Vector<i32>> {
+        let ((left, top), (right, bottom)) = vertices[1..].iter().fold(
+            (vertices[0].split(), vertices[0].split()),
+            |((left, top), (right, bottom)), value| {
+                let left = left.min(value.x());
+                let right = right.max(value.x());
+                let top = top.min(value.y());
+                let bottom = bottom.max(value.y());
+                ((left, top), (right, bottom))
+            },
+        );
+
+        let mut segments = vertices
+            .windows(2)
+            .map(|v| (v[0], v[1]))
+            .collect::<Vec<_>>();
+        segments.push((*vertices.last().unwrap(), vertices[0]));
+        let mut pixels = Vec::new();
+        for y in top..=bottom {
+            let mut segments = segments
+                .iter()
+                .filter(|(a, b)| (y >= a.y() && y <= b.y()) || (y >= b.y() && y <= a.y()))
+                .map(|(a, b)| (a, b, false, false, line_scan(a, b, y)))
+                .collect::<Vec<_>>();
+
+            let mut counter = false;
+            for x in left..=right {
+                let mut should_paint = false;
+                let mut intersections = 0;
+                for (a, b, intersected, was_intersected, scan) in segments.iter_mut() {
+                    if x >= *scan.start() && x <= *scan.end() {
+                        should_paint = true;
+                        *intersected = true;
+                        if !*was_intersected && (y < a.y() || y < b.y()) {
+                            intersections += 1;
+                        }
+                    } else {
+                        *intersected = false;
+                    }
+                    *was_intersected = *intersected;
+                }
+
+                if intersections % 2 == 1 {
+                    counter = !counter;
+                }
+
+                if should_paint || counter {
+                    pixels.push(Vector::new(x, y));
+                }
+            }
+        }
+        pixels.sort();
+        pixels
+    }
+
+    fn painted(draw: impl FnOnce(&mut Painter<Canvas<u32>>)) -> Vec<Vector<i32>> {
+        let mut canvas = Canvas::with_resolution(0, 32, 32);
+        draw(&mut canvas.painter());
+        let mut pixels = Vec::new();
+        for y in 0..32 {
+            for x in 0..32 {
+                let hits = *canvas.pixel((x, y).into()).unwrap();
+                assert!(hits <= 1, "pixel ({}, {}) painted {} times", x, y, hits);
+                if hits == 1 {
+                    pixels.push(Vector::new(x, y));
+                }
+            }
+        }
+        pixels.sort();
+        pixels
+    }
+
+    const CONVEX: [&[(i32, i32)]; 5] = [
+        &[(1, 1), (14, 2), (8, 13)],
+        &[(2, 2), (13, 2), (13, 13), (2, 13)],
+        &[(10, 11), (27, 7), (23, 28), (11, 30)],
+        &[(1, 5), (24, 1), (25, 1), (30, 5), (20, 25)],
+        &[(16, 1), (30, 9), (28, 24), (5, 30), (1, 12)],
+    ];
+
+    #[test]
+    fn polygon_f_matches_previous_filler() {
+        for vertices in CONVEX {
+            let vertices: Vec<Vector<i32>> = vertices.iter().map(|&v| v.into()).collect();
+            let filled = painted(|painter| painter.polygon_f(&vertices, |_, _, hits| hits + 1));
+            assert_eq!(filled, sane_polygon(&vertices), "{:?}", vertices);
+        }
+    }
+
+    #[test]
+    fn polygon_f_covers_polygon() {
+        for vertices in CONVEX {
+            let outline = painted(|painter| painter.polygon(vertices, |_, _, _| 1));
+            let filled = painted(|painter| painter.polygon_f(vertices, |_, _, hits| hits + 1));
+            assert!(outline.iter().all(|pixel| filled.contains(pixel)));
+        }
+    }
+
+    #[test]
+    fn contours_f_matches_polygon_f() {
+        let star = [(3, 0), (6, 14), (9, 2), (12, 14), (15, 0), (0, 7)];
+        for vertices in CONVEX.iter().copied().chain([&star[..]]) {
+            let polygon = painted(|painter| painter.polygon_f(vertices, |_, _, hits| hits + 1));
+            let contours = painted(|painter| {
+                painter.contours_f(&[vertices], FillRule::EvenOdd, |_, _, hits| hits + 1)
+            });
+            assert_eq!(polygon, contours);
+        }
+    }
+}
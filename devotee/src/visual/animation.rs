@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use super::sheet::SpriteSheet;
+use super::{Flip, Image, ImageMut, Painter};
+use crate::util::vector::Vector;
+
+/// Playback mode of the animation clip.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Playback {
+    /// Start over after the last frame.
+    #[default]
+    Loop,
+    /// Play frames forward, then backward, then repeat.
+    PingPong,
+    /// Stop at the last frame.
+    Once,
+}
+
+/// Sequence of sprite sheet frames with individual durations.
+#[derive(Clone, Debug)]
+pub struct Clip {
+    frames: Vec<(usize, Duration)>,
+    playback: Playback,
+}
+
+impl Clip {
+    /// Create new clip with given frame indices, their durations and playback mode.
+    pub fn new(frames: Vec<(usize, Duration)>, playback: Playback) -> Self {
+        Self { frames, playback }
+    }
+
+    /// Create new clip with given frame indices of equal duration and playback mode.
+    pub fn uniform<F>(frames: F, duration: Duration, playback: Playback) -> Self
+    where
+        F: IntoIterator<Item = usize>,
+    {
+        let frames = frames.into_iter().map(|index| (index, duration)).collect();
+        Self { frames, playback }
+    }
+
+    /// Get frame indices and their durations.
+    pub fn frames(&self) -> &[(usize, Duration)] {
+        &self.frames
+    }
+
+    /// Get playback mode.
+    pub fn playback(&self) -> Playback {
+        self.playback
+    }
+
+    /// Get duration of a single pass through all frames.
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// Check if the clip is over after `elapsed` time.
+    /// Looping clips are never over.
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        self.playback == Playback::Once && elapsed >= self.duration()
+    }
+
+    /// Get sprite sheet frame index to be displayed after `elapsed` time.
+    pub fn frame_at(&self, elapsed: Duration) -> Option<usize> {
+        let last = self.frames.len().checked_sub(1)?;
+        let steps = match self.playback {
+            Playback::PingPong if last > 1 => (0..=last).chain((1..last).rev()).collect(),
+            _ => (0..=last).collect::<Vec<_>>(),
+        };
+        let total: Duration = steps.iter().map(|step| self.frames[*step].1).sum();
+        if total.is_zero() {
+            return Some(self.frames[0].0);
+        }
+
+        let mut time = match self.playback {
+            Playback::Once if elapsed >= total => return Some(self.frames[last].0),
+            Playback::Once => elapsed,
+            Playback::Loop | Playback::PingPong => {
+                Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64)
+            }
+        };
+        for step in steps {
+            let (index, duration) = self.frames[step];
+            if time < duration {
+                return Some(index);
+            }
+            time -= duration;
+        }
+        Some(self.frames[last].0)
+    }
+}
+
+/// Animation player with a set of named clips.
+/// It is advanced with update delta, e.g. `Context::delta()`.
+#[derive(Clone, Debug, Default)]
+pub struct Animation {
+    clips: HashMap<String, Clip>,
+    current: Option<String>,
+    elapsed: Duration,
+}
+
+impl Animation {
+    /// Create new animation without clips.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get new animation with the `clip` added under the `name`.
+    /// The first added clip is played.
+    pub fn with_clip<N: Into<String>>(self, name: N, clip: Clip) -> Self {
+        let mut result = self;
+        result.add_clip(name, clip);
+        result
+    }
+
+    /// Add the `clip` under the `name`, replacing the previous one if any.
+    /// The first added clip is played.
+    pub fn add_clip<N: Into<String>>(&mut self, name: N, clip: Clip) {
+        let name = name.into();
+        if self.current.is_none() {
+            self.current = Some(name.clone());
+        }
+        self.clips.insert(name, clip);
+    }
+
+    /// Get clip by name.
+    pub fn clip(&self, name: &str) -> Option<&Clip> {
+        self.clips.get(name)
+    }
+
+    /// Get name of the clip being played.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Switch to the clip with given name.
+    /// Nothing happens if the clip is already playing.
+    /// Returns `false` if there is no such clip.
+    pub fn play(&mut self, name: &str) -> bool {
+        if !self.clips.contains_key(name) {
+            return false;
+        }
+        if self.current.as_deref() != Some(name) {
+            self.current = Some(name.to_owned());
+            self.elapsed = Duration::ZERO;
+        }
+        true
+    }
+
+    /// Restart current clip from the first frame.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// Advance current clip by `delta` time.
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    /// Get time elapsed since the current clip start.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Check if the current clip is over.
+    pub fn is_finished(&self) -> bool {
+        self.current_clip()
+            .map(|clip| clip.is_finished(self.elapsed))
+            .unwrap_or(true)
+    }
+
+    /// Get sprite sheet frame index to be displayed.
+    pub fn frame(&self) -> Option<usize> {
+        self.current_clip()?.frame_at(self.elapsed)
+    }
+
+    /// Draw current frame from the `sheet` with provided painter and function.
+    /// Returns `false` if there is nothing to draw.
+    pub fn draw<'b, T, I, A, F, P>(
+        &self,
+        painter: &mut Painter<T>,
+        at: A,
+        sheet: &'b SpriteSheet<I>,
+        flip: Flip,
+        function: F,
+    ) -> bool
+    where
+        T: ImageMut<Pixel = P>,
+        P: Clone,
+        I: Image,
+        A: Into<Vector<i32>>,
+        I::Pixel: Clone,
+        I::PixelRef<'b>: Deref<Target = I::Pixel>,
+        F: FnMut(i32, i32, P, i32, i32, I::Pixel) -> P,
+        for<'c> T::PixelRef<'c>: Deref<Target = P>,
+        for<'c> T::PixelMut<'c>: DerefMut<Target = P>,
+    {
+        match self.frame() {
+            Some(frame) => sheet.draw(painter, at, frame, flip, function),
+            None => false,
+        }
+    }
+
+    fn current_clip(&self) -> Option<&Clip> {
+        self.clips.get(self.current.as_deref()?)
+    }
+}
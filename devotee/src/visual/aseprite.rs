@@ -0,0 +1,828 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::time::Duration;
+
+use super::animation::{Animation, Clip, Playback};
+use super::canvas::Canvas;
+use super::sheet::SpriteSheet;
+use super::{Image, ImageMut};
+use crate::util::vector::Vector;
+
+const FILE_MAGIC: u16 = 0xa5e0;
+const FRAME_MAGIC: u16 = 0xf1fa;
+const HEADER_SIZE: usize = 128;
+// Upper bound of pixels in a single image, composed frame or sprite sheet.
+const MAX_PIXELS: usize = 1 << 26;
+// Indexed color mode can't address more colors.
+const MAX_PALETTE: usize = 256;
+// Flags word and RGBA color of a palette entry without a name.
+const PALETTE_ENTRY_SIZE: usize = 2 + 4;
+
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_REFERENCE: u16 = 64;
+const LAYER_NORMAL: u16 = 0;
+const LAYER_GROUP: u16 = 1;
+
+const CEL_RAW: u16 = 0;
+const CEL_LINKED: u16 = 1;
+const CEL_COMPRESSED: u16 = 2;
+
+/// Error of Aseprite file loading.
+#[derive(Debug)]
+pub enum Error {
+    /// File can't be read.
+    Io(std::io::Error),
+    /// Data ends unexpectedly.
+    UnexpectedEnd,
+    /// File or frame magic number mismatch.
+    BadMagic,
+    /// Only indexed color mode is supported, the value is the color depth in bits.
+    UnsupportedColorDepth(u16),
+    /// Compressed cel data is malformed.
+    Decompression,
+    /// Linked cel points to nonexistent cel.
+    BrokenLink,
+    /// Data is inconsistent, the value describes the inconsistent data.
+    Malformed(&'static str),
+    /// Data exceeds supported size limits, the value describes the oversized data.
+    TooLarge(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "failed to read file: {}", error),
+            Error::UnexpectedEnd => write!(f, "unexpected end of data"),
+            Error::BadMagic => write!(f, "magic number mismatch"),
+            Error::UnsupportedColorDepth(depth) => {
+                write!(f, "unsupported color depth: {} bits", depth)
+            }
+            Error::Decompression => write!(f, "malformed compressed cel"),
+            Error::BrokenLink => write!(f, "linked cel points to nothing"),
+            Error::Malformed(what) => write!(f, "malformed document: {}", what),
+            Error::TooLarge(what) => write!(f, "too large: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .position
+            .checked_add(count)
+            .ok_or(Error::UnexpectedEnd)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(Error::UnexpectedEnd)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), Error> {
+        self.bytes(count).map(|_| ())
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, Error> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, Error> {
+        Ok(self.word()? as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, Error> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let length = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(length)?).into_owned())
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position..];
+        self.position = self.data.len();
+        rest
+    }
+}
+
+/// Aseprite document layer.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    name: String,
+    visible: bool,
+    drawable: bool,
+    parent: Option<usize>,
+}
+
+impl Layer {
+    /// Get layer name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if the layer itself is marked visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Get index of the parent group layer.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// Playback direction of the tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Play frames from first to last.
+    Forward,
+    /// Play frames from last to first.
+    Reverse,
+    /// Play frames forward, then backward.
+    PingPong,
+    /// Play frames backward, then forward.
+    PingPongReverse,
+}
+
+/// Named range of frames.
+#[derive(Clone, Debug)]
+pub struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: Direction,
+    repeat: u16,
+}
+
+impl Tag {
+    /// Get tag name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get first frame index.
+    pub fn from(&self) -> usize {
+        self.from
+    }
+
+    /// Get last frame index, inclusive.
+    pub fn to(&self) -> usize {
+        self.to
+    }
+
+    /// Get playback direction.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Get count of repeats, zero means infinite.
+    pub fn repeat(&self) -> u16 {
+        self.repeat
+    }
+
+    /// Get animation clip of this tag with frame durations of the `aseprite` document.
+    /// Single repeat is played once, any other repeat count is played in a loop.
+    pub fn clip(&self, aseprite: &Aseprite) -> Clip {
+        let range = self.from..=self.to.min(aseprite.frames.len().saturating_sub(1));
+        let mut frames: Vec<_> = range
+            .map(|index| (index, aseprite.frames[index].duration))
+            .collect();
+        if let Direction::Reverse | Direction::PingPongReverse = self.direction {
+            frames.reverse();
+        }
+        let playback = match self.direction {
+            Direction::PingPong | Direction::PingPongReverse => Playback::PingPong,
+            _ if self.repeat == 1 => Playback::Once,
+            _ => Playback::Loop,
+        };
+        Clip::new(frames, playback)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Cel {
+    layer: usize,
+    position: Vector<i32>,
+    size: Vector<i32>,
+    data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    duration: Duration,
+    cels: Vec<Cel>,
+}
+
+/// Indexed color Aseprite document.
+#[derive(Clone, Debug)]
+pub struct Aseprite {
+    width: usize,
+    height: usize,
+    transparent: u8,
+    palette: Vec<[u8; 4]>,
+    layers: Vec<Layer>,
+    frames: Vec<Frame>,
+    tags: Vec<Tag>,
+}
+
+impl Aseprite {
+    /// Load document from `.aseprite` or `.ase` file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Parse document from raw file data.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader::new(data);
+        let header = reader.bytes(HEADER_SIZE)?;
+        let mut header = Reader::new(header);
+
+        header.dword()?;
+        if header.word()? != FILE_MAGIC {
+            return Err(Error::BadMagic);
+        }
+        let frame_count = header.word()? as usize;
+        let width = header.word()? as usize;
+        let height = header.word()? as usize;
+        let depth = header.word()?;
+        if depth != 8 {
+            return Err(Error::UnsupportedColorDepth(depth));
+        }
+        header.skip(4 + 2 + 4 + 4)?;
+        let transparent = header.byte()?;
+        if frame_count == 0 {
+            return Err(Error::Malformed("frame count"));
+        }
+        if width * height > MAX_PIXELS {
+            return Err(Error::TooLarge("document size"));
+        }
+
+        let mut document = Self {
+            width,
+            height,
+            transparent,
+            palette: Vec::new(),
+            layers: Vec::new(),
+            frames: Vec::with_capacity(frame_count),
+            tags: Vec::new(),
+        };
+        let mut groups: Vec<usize> = Vec::new();
+
+        for _ in 0..frame_count {
+            let size = reader.dword()? as usize;
+            let mut frame = Reader::new(reader.bytes(size.saturating_sub(4))?);
+            if frame.word()? != FRAME_MAGIC {
+                return Err(Error::BadMagic);
+            }
+            let old_chunks = frame.word()? as usize;
+            let duration = Duration::from_millis(frame.word()? as u64);
+            frame.skip(2)?;
+            let chunks = match frame.dword()? as usize {
+                0 => old_chunks,
+                chunks => chunks,
+            };
+
+            let mut cels = Vec::new();
+            for _ in 0..chunks {
+                let size = frame.dword()? as usize;
+                let kind = frame.word()?;
+                let mut chunk = Reader::new(frame.bytes(size.saturating_sub(6))?);
+                match kind {
+                    LAYER_CHUNK => {
+                        let index = document.layers.len();
+                        let layer = Self::parse_layer(&mut chunk, index, &mut groups)?;
+                        document.layers.push(layer);
+                    }
+                    CEL_CHUNK => {
+                        if let Some(cel) = document.parse_cel(&mut chunk)? {
+                            cels.push(cel);
+                        }
+                    }
+                    TAGS_CHUNK => document.tags = Self::parse_tags(&mut chunk)?,
+                    PALETTE_CHUNK => document.parse_palette(&mut chunk)?,
+                    _ => {}
+                }
+            }
+            document.frames.push(Frame { duration, cels });
+        }
+        Ok(document)
+    }
+
+    fn parse_layer(
+        reader: &mut Reader,
+        index: usize,
+        groups: &mut Vec<usize>,
+    ) -> Result<Layer, Error> {
+        let flags = reader.word()?;
+        let kind = reader.word()?;
+        let level = reader.word()? as usize;
+        reader.skip(2 + 2 + 2 + 1 + 3)?;
+        let name = reader.string()?;
+
+        groups.truncate(level);
+        let parent = level
+            .checked_sub(1)
+            .and_then(|level| groups.get(level).copied());
+        if kind == LAYER_GROUP {
+            groups.push(index);
+        }
+        Ok(Layer {
+            name,
+            visible: flags & LAYER_VISIBLE != 0,
+            drawable: kind == LAYER_NORMAL && flags & LAYER_REFERENCE == 0,
+            parent,
+        })
+    }
+
+    fn parse_cel(&self, reader: &mut Reader) -> Result<Option<Cel>, Error> {
+        let layer = reader.word()? as usize;
+        let x = reader.short()? as i32;
+        let y = reader.short()? as i32;
+        reader.skip(1)?;
+        let kind = reader.word()?;
+        reader.skip(2 + 5)?;
+        let position = Vector::new(x, y);
+
+        match kind {
+            CEL_RAW | CEL_COMPRESSED => {
+                let width = reader.word()? as usize;
+                let height = reader.word()? as usize;
+                let size = Vector::new(width as i32, height as i32);
+                let length = width * height;
+                if length > MAX_PIXELS {
+                    return Err(Error::TooLarge("cel size"));
+                }
+                let data = if kind == CEL_RAW {
+                    reader.bytes(length)?.to_vec()
+                } else {
+                    miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(reader.rest(), length)
+                        .map_err(|_| Error::Decompression)?
+                };
+                if data.len() < length {
+                    return Err(Error::Decompression);
+                }
+                Ok(Some(Cel {
+                    layer,
+                    position,
+                    size,
+                    data,
+                }))
+            }
+            CEL_LINKED => {
+                let linked = reader.word()? as usize;
+                let cel = self
+                    .frames
+                    .get(linked)
+                    .and_then(|frame| frame.cels.iter().find(|cel| cel.layer == layer))
+                    .ok_or(Error::BrokenLink)?;
+                Ok(Some(Cel {
+                    position,
+                    ..cel.clone()
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_tags(reader: &mut Reader) -> Result<Vec<Tag>, Error> {
+        let count = reader.word()?;
+        reader.skip(8)?;
+        let mut tags = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let from = reader.word()? as usize;
+            let to = reader.word()? as usize;
+            let direction = match reader.byte()? {
+                1 => Direction::Reverse,
+                2 => Direction::PingPong,
+                3 => Direction::PingPongReverse,
+                _ => Direction::Forward,
+            };
+            let repeat = reader.word()?;
+            reader.skip(6 + 3 + 1)?;
+            let name = reader.string()?;
+            tags.push(Tag {
+                name,
+                from,
+                to,
+                direction,
+                repeat,
+            });
+        }
+        Ok(tags)
+    }
+
+    fn parse_palette(&mut self, reader: &mut Reader) -> Result<(), Error> {
+        let size = reader.dword()? as usize;
+        let first = reader.dword()? as usize;
+        let last = reader.dword()? as usize;
+        reader.skip(8)?;
+        if size > MAX_PALETTE {
+            return Err(Error::TooLarge("palette size"));
+        }
+        if first > last || last >= size {
+            return Err(Error::Malformed("palette range"));
+        }
+        if (last - first + 1) * PALETTE_ENTRY_SIZE > reader.remaining() {
+            return Err(Error::UnexpectedEnd);
+        }
+        if self.palette.len() < size {
+            self.palette.resize(size, [0; 4]);
+        }
+        for index in first..=last {
+            let flags = reader.word()?;
+            let color = reader.bytes(4)?;
+            if let Some(entry) = self.palette.get_mut(index) {
+                entry.copy_from_slice(color);
+            }
+            if flags & 1 != 0 {
+                reader.string()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get document width.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get document height.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get palette index treated as transparent.
+    pub fn transparent(&self) -> u8 {
+        self.transparent
+    }
+
+    /// Get palette colors in RGBA format.
+    pub fn palette(&self) -> &[[u8; 4]] {
+        &self.palette
+    }
+
+    /// Get layers from bottom to top.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Get animation tags.
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Get count of frames.
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Get duration of specific frame.
+    pub fn duration(&self, frame: usize) -> Option<Duration> {
+        self.frames.get(frame).map(|frame| frame.duration)
+    }
+
+    fn is_layer_visible(&self, layer: usize) -> bool {
+        let mut current = self.layers.get(layer);
+        while let Some(layer) = current {
+            if !layer.visible {
+                return false;
+            }
+            current = layer.parent.and_then(|parent| self.layers.get(parent));
+        }
+        true
+    }
+
+    fn compose<P, F>(&self, frame: usize, mut filter: F) -> Option<Canvas<P>>
+    where
+        P: Clone + From<u8>,
+        F: FnMut(usize) -> bool,
+    {
+        let frame = self.frames.get(frame)?;
+        let mut canvas =
+            Canvas::with_resolution(P::from(self.transparent), self.width, self.height);
+        for layer in 0..self.layers.len() {
+            if !self.layers[layer].drawable || !filter(layer) {
+                continue;
+            }
+            for cel in frame.cels.iter().filter(|cel| cel.layer == layer) {
+                let width = cel.size.x() as usize;
+                for y in 0..cel.size.y() {
+                    for x in 0..cel.size.x() {
+                        let Some(&index) = cel.data.get(x as usize + y as usize * width) else {
+                            continue;
+                        };
+                        if index == self.transparent {
+                            continue;
+                        }
+                        if let Some(pixel) = canvas.pixel_mut(cel.position + (x, y)) {
+                            *pixel = P::from(index);
+                        }
+                    }
+                }
+            }
+        }
+        Some(canvas)
+    }
+
+    /// Get specific frame with all visible layers combined.
+    /// Transparent pixels are filled with transparent palette index.
+    pub fn frame<P>(&self, frame: usize) -> Option<Canvas<P>>
+    where
+        P: Clone + From<u8>,
+    {
+        self.compose(frame, |layer| self.is_layer_visible(layer))
+    }
+
+    /// Get specific layer of specific frame regardless of its visibility.
+    pub fn layer<P>(&self, frame: usize, layer: usize) -> Option<Canvas<P>>
+    where
+        P: Clone + From<u8>,
+    {
+        self.layers.get(layer)?;
+        self.compose(frame, |current| current == layer)
+    }
+
+    /// Get sprite sheet with all frames placed left to right.
+    /// Returns `None` if the sheet would be too large.
+    pub fn sprite_sheet<P>(&self) -> Option<SpriteSheet<Canvas<P>>>
+    where
+        P: Clone + From<u8>,
+    {
+        let width = self.width.checked_mul(self.frames.len())?;
+        if width.checked_mul(self.height)? > MAX_PIXELS {
+            return None;
+        }
+        let mut image = Canvas::with_resolution(P::from(self.transparent), width, self.height);
+        for index in 0..self.frames.len() {
+            if let Some(frame) = self.frame::<P>(index) {
+                let offset = Vector::new((index * self.width) as i32, 0);
+                for y in 0..frame.height() {
+                    for x in 0..frame.width() {
+                        if let (Some(target), Some(source)) =
+                            (image.pixel_mut(offset + (x, y)), frame.pixel((x, y).into()))
+                        {
+                            *target = source.clone();
+                        }
+                    }
+                }
+            }
+        }
+        Some(SpriteSheet::with_grid(
+            image,
+            (self.width as i32, self.height as i32),
+        ))
+    }
+
+    /// Get animation with a clip for every tag.
+    /// If there are no tags, a single looping clip of all frames is named with an empty string.
+    pub fn animation(&self) -> Animation {
+        let mut animation = Animation::new();
+        if self.tags.is_empty() {
+            let frames = self
+                .frames
+                .iter()
+                .enumerate()
+                .map(|(index, frame)| (index, frame.duration))
+                .collect();
+            animation.add_clip("", Clip::new(frames, Playback::Loop));
+        }
+        for tag in self.tags.iter() {
+            animation.add_clip(tag.name.clone(), tag.clip(self));
+        }
+        animation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&(data.len() as u32 + 6).to_le_bytes());
+        chunk.extend_from_slice(&kind.to_le_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    fn layer(name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&LAYER_VISIBLE.to_le_bytes());
+        data.extend_from_slice(&LAYER_NORMAL.to_le_bytes());
+        data.extend_from_slice(&[0; 2 + 2 + 2 + 2 + 1 + 3]);
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        chunk(LAYER_CHUNK, &data)
+    }
+
+    fn cel(
+        layer: u16,
+        position: (i16, i16),
+        kind: u16,
+        size: (u16, u16),
+        pixels: &[u8],
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&layer.to_le_bytes());
+        data.extend_from_slice(&position.0.to_le_bytes());
+        data.extend_from_slice(&position.1.to_le_bytes());
+        data.push(0xff);
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.extend_from_slice(&[0; 2 + 5]);
+        data.extend_from_slice(&size.0.to_le_bytes());
+        data.extend_from_slice(&size.1.to_le_bytes());
+        if kind == CEL_COMPRESSED {
+            data.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(pixels, 6));
+        } else {
+            data.extend_from_slice(pixels);
+        }
+        chunk(CEL_CHUNK, &data)
+    }
+
+    fn palette(colors: &[[u8; 4]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(colors.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.extend_from_slice(&(colors.len() as u32 - 1).to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        for color in colors {
+            data.extend_from_slice(&0_u16.to_le_bytes());
+            data.extend_from_slice(color);
+        }
+        chunk(PALETTE_CHUNK, &data)
+    }
+
+    fn document(size: (u16, u16), chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&FRAME_MAGIC.to_le_bytes());
+        frame.extend_from_slice(&(chunks.len() as u16).to_le_bytes());
+        frame.extend_from_slice(&100_u16.to_le_bytes());
+        frame.extend_from_slice(&[0; 2]);
+        frame.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        for chunk in chunks {
+            frame.extend_from_slice(chunk);
+        }
+
+        let mut header = vec![0; HEADER_SIZE];
+        header[4..6].copy_from_slice(&FILE_MAGIC.to_le_bytes());
+        header[6..8].copy_from_slice(&1_u16.to_le_bytes());
+        header[8..10].copy_from_slice(&size.0.to_le_bytes());
+        header[10..12].copy_from_slice(&size.1.to_le_bytes());
+        header[12..14].copy_from_slice(&8_u16.to_le_bytes());
+
+        let mut data = header;
+        data.extend_from_slice(&(frame.len() as u32 + 4).to_le_bytes());
+        data.extend_from_slice(&frame);
+        let length = data.len() as u32;
+        data[0..4].copy_from_slice(&length.to_le_bytes());
+        data
+    }
+
+    fn fixture() -> Vec<u8> {
+        document(
+            (4, 2),
+            &[
+                layer("raw"),
+                layer("compressed"),
+                palette(&[[0, 0, 0, 0], [255, 0, 0, 255], [0, 255, 0, 255]]),
+                cel(0, (0, 0), CEL_RAW, (2, 2), &[1, 2, 2, 1]),
+                cel(1, (1, 1), CEL_COMPRESSED, (3, 1), &[0, 2, 1]),
+            ],
+        )
+    }
+
+    #[test]
+    fn parses_minimal_document() {
+        let aseprite = Aseprite::from_bytes(&fixture()).unwrap();
+        assert_eq!((aseprite.width(), aseprite.height()), (4, 2));
+        assert_eq!(aseprite.frame_count(), 1);
+        assert_eq!(aseprite.duration(0), Some(Duration::from_millis(100)));
+        assert_eq!(aseprite.layers().len(), 2);
+        assert_eq!(aseprite.layers()[1].name(), "compressed");
+        assert_eq!(aseprite.palette()[1], [255, 0, 0, 255]);
+
+        let frame = aseprite.frame::<u8>(0).unwrap();
+        let pixels: Vec<u8> = (0..2)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .map(|(x, y)| *frame.pixel((x, y).into()).unwrap())
+            .collect();
+        assert_eq!(pixels, [1, 2, 0, 0, 2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn truncated_document_is_error() {
+        let data = fixture();
+        for length in 0..data.len() {
+            assert!(Aseprite::from_bytes(&data[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn oversized_cel_is_error() {
+        for kind in [CEL_RAW, CEL_COMPRESSED] {
+            let data = document(
+                (4, 2),
+                &[
+                    layer("huge"),
+                    cel(0, (0, 0), kind, (u16::MAX, u16::MAX), &[1; 4]),
+                ],
+            );
+            assert!(Aseprite::from_bytes(&data).is_err());
+        }
+    }
+
+    #[test]
+    fn oversized_document_is_error() {
+        let data = document((u16::MAX, u16::MAX), &[]);
+        assert!(matches!(
+            Aseprite::from_bytes(&data),
+            Err(Error::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn zero_frames_is_error() {
+        let mut data = fixture();
+        data[6..8].copy_from_slice(&0_u16.to_le_bytes());
+        assert!(matches!(
+            Aseprite::from_bytes(&data),
+            Err(Error::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn oversized_palette_is_error() {
+        let palette = |size: u32, first: u32, last: u32| {
+            let mut data = Vec::new();
+            for value in [size, first, last] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            data.extend_from_slice(&[0; 8 + 6]);
+            document((4, 2), &[chunk(PALETTE_CHUNK, &data)])
+        };
+        let result = Aseprite::from_bytes(&palette(u32::MAX, 0, 0));
+        assert!(matches!(result, Err(Error::TooLarge(_))));
+        let result = Aseprite::from_bytes(&palette(4, 0, 4));
+        assert!(matches!(result, Err(Error::Malformed(_))));
+        let result = Aseprite::from_bytes(&palette(4, 3, 0));
+        assert!(matches!(result, Err(Error::Malformed(_))));
+        let result = Aseprite::from_bytes(&palette(256, 0, 255));
+        assert!(matches!(result, Err(Error::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn sprite_sheet_has_every_frame() {
+        let aseprite = Aseprite::from_bytes(&fixture()).unwrap();
+        let sheet = aseprite.sprite_sheet::<u8>().unwrap();
+        assert_eq!(sheet.len(), 1);
+        assert_eq!(sheet.bounds(0), Some(((0, 0).into(), (4, 2).into())));
+    }
+
+    #[test]
+    fn bad_magic_is_error() {
+        let mut data = fixture();
+        data[4] = 0;
+        assert!(matches!(Aseprite::from_bytes(&data), Err(Error::BadMagic)));
+    }
+}
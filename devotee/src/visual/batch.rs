@@ -0,0 +1,111 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Flip, Image, ImageMut, Painter};
+use crate::util::vector::Vector;
+
+type Function<'a, P, O> = Box<dyn 'a + FnMut(i32, i32, P, i32, i32, O) -> P>;
+
+struct Entry<'a, U, P>
+where
+    U: Image + ?Sized,
+{
+    image: &'a U,
+    position: Vector<i32>,
+    z: i32,
+    flip: Flip,
+    function: Function<'a, P, U::Pixel>,
+}
+
+/// Draw list of images sorted by z-layer.
+/// Entries with lower `z` are drawn first, entries with equal `z` are drawn in submission order.
+/// Entries outside of the painter clip rectangle are skipped.
+pub struct Batch<'a, U, P>
+where
+    U: Image + ?Sized,
+{
+    entries: Vec<Entry<'a, U, P>>,
+}
+
+impl<'a, U, P> Batch<'a, U, P>
+where
+    U: Image + ?Sized,
+{
+    /// Create new empty batch.
+    pub fn new() -> Self {
+        let entries = Vec::new();
+        Self { entries }
+    }
+
+    /// Get count of submitted entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no submitted entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove all submitted entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Submit image to be drawn at `position` on `z` layer with provided function.
+    pub fn push<I, F>(&mut self, image: &'a U, position: I, z: i32, flip: Flip, function: F)
+    where
+        I: Into<Vector<i32>>,
+        F: 'a + FnMut(i32, i32, P, i32, i32, U::Pixel) -> P,
+    {
+        let position = position.into();
+        let function = Box::new(function);
+        self.entries.push(Entry {
+            image,
+            position,
+            z,
+            flip,
+            function,
+        });
+    }
+
+    /// Draw all submitted entries with provided painter and clear the batch.
+    /// Returns count of entries actually drawn.
+    pub fn draw<T>(&mut self, painter: &mut Painter<T>) -> usize
+    where
+        T: ImageMut<Pixel = P>,
+        P: Clone,
+        U::Pixel: Clone,
+        U::PixelRef<'a>: Deref<Target = U::Pixel>,
+        for<'b> T::PixelRef<'b>: Deref<Target = P>,
+        for<'b> T::PixelMut<'b>: DerefMut<Target = P>,
+    {
+        let offset = painter.offset();
+        let (clip_from, clip_to) = painter.clip();
+        let mut drawn = 0;
+
+        self.entries.sort_by_key(|entry| entry.z);
+        for entry in self.entries.drain(..) {
+            let start = entry.position + offset;
+            let end = start + entry.image.dimensions();
+            if end.x() <= clip_from.x()
+                || end.y() <= clip_from.y()
+                || start.x() >= clip_to.x()
+                || start.y() >= clip_to.y()
+            {
+                continue;
+            }
+            painter.image_flipped(entry.position, entry.image, entry.flip, entry.function);
+            drawn += 1;
+        }
+        drawn
+    }
+}
+
+impl<'a, U, P> Default for Batch<'a, U, P>
+where
+    U: Image + ?Sized,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
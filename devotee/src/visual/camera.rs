@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use crate::util::affine::Affine;
+use crate::util::vector::Vector;
+
+/// Camera looking at the world through the viewport of fixed pixel size.
+/// It produces offset and transform for `Painter` and converts coordinates between world and screen.
+#[derive(Clone, Debug)]
+pub struct Camera {
+    center: Vector<f32>,
+    viewport: Vector<i32>,
+    zoom: i32,
+    dead_zone: Vector<f32>,
+    smoothing: f32,
+    bounds: Option<(Vector<f32>, Vector<f32>)>,
+    shake: f32,
+    shake_decay: f32,
+    shake_offset: Vector<f32>,
+    seed: u32,
+}
+
+impl Camera {
+    /// Create new camera with given `viewport` size in screen pixels.
+    /// Without zoom the world origin is initially placed at the top left corner of the viewport.
+    pub fn new<I: Into<Vector<i32>>>(viewport: I) -> Self {
+        let viewport = viewport.into();
+        Self {
+            center: Vector::new(viewport.x() as f32 / 2.0, viewport.y() as f32 / 2.0),
+            viewport,
+            zoom: 1,
+            dead_zone: Vector::new(0.0, 0.0),
+            smoothing: 0.0,
+            bounds: None,
+            shake: 0.0,
+            shake_decay: 0.0,
+            shake_offset: Vector::new(0.0, 0.0),
+            seed: 0x9e37_79b9,
+        }
+    }
+
+    /// Get new camera with desired integer zoom.
+    pub fn with_zoom(self, zoom: i32) -> Self {
+        let mut result = self;
+        result.set_zoom(zoom);
+        result
+    }
+
+    /// Get new camera with desired dead zone size in world pixels.
+    /// The followed target can move freely inside the dead zone without moving the camera.
+    pub fn with_dead_zone<I: Into<Vector<f32>>>(self, size: I) -> Self {
+        Self {
+            dead_zone: size.into() / 2.0,
+            ..self
+        }
+    }
+
+    /// Get new camera with desired follow smoothing rate per second.
+    /// Zero rate makes the camera snap to the target immediately.
+    pub fn with_smoothing(self, rate: f32) -> Self {
+        Self {
+            smoothing: rate.max(0.0),
+            ..self
+        }
+    }
+
+    /// Get new camera limited to show only world area in `[from, to)` range.
+    pub fn with_bounds<I: Into<Vector<f32>>>(self, from: I, to: I) -> Self {
+        let mut result = self;
+        result.set_bounds(Some((from.into(), to.into())));
+        result
+    }
+
+    /// Get viewport size in screen pixels.
+    pub fn viewport(&self) -> Vector<i32> {
+        self.viewport
+    }
+
+    /// Set viewport size in screen pixels.
+    pub fn set_viewport<I: Into<Vector<i32>>>(&mut self, viewport: I) {
+        self.viewport = viewport.into();
+        self.clamp();
+    }
+
+    /// Get integer zoom.
+    pub fn zoom(&self) -> i32 {
+        self.zoom
+    }
+
+    /// Set integer zoom, it is never less than one.
+    pub fn set_zoom(&mut self, zoom: i32) {
+        self.zoom = zoom.max(1);
+        self.clamp();
+    }
+
+    /// Get world bounds.
+    pub fn bounds(&self) -> Option<(Vector<f32>, Vector<f32>)> {
+        self.bounds
+    }
+
+    /// Set or remove world bounds.
+    pub fn set_bounds(&mut self, bounds: Option<(Vector<f32>, Vector<f32>)>) {
+        self.bounds = bounds;
+        self.clamp();
+    }
+
+    /// Get world position in the center of the view, not affected by shake.
+    pub fn center(&self) -> Vector<f32> {
+        self.center
+    }
+
+    /// Move view center to the world `position` immediately.
+    pub fn set_center<I: Into<Vector<f32>>>(&mut self, position: I) {
+        self.center = position.into();
+        self.clamp();
+    }
+
+    /// Get size of the visible world area in world pixels.
+    pub fn view_size(&self) -> Vector<i32> {
+        self.viewport / self.zoom
+    }
+
+    /// Move the camera towards the `target` world position, keeping it inside the dead zone.
+    pub fn follow<I: Into<Vector<f32>>>(&mut self, target: I, delta: Duration) {
+        let target = target.into();
+        let distance = target - self.center;
+        let pull = Vector::new(
+            distance.x() - distance.x().clamp(-self.dead_zone.x(), self.dead_zone.x()),
+            distance.y() - distance.y().clamp(-self.dead_zone.y(), self.dead_zone.y()),
+        );
+        let factor = if self.smoothing > 0.0 {
+            1.0 - (-self.smoothing * delta.as_secs_f32()).exp()
+        } else {
+            1.0
+        };
+        self.center += pull * factor;
+        self.clamp();
+    }
+
+    /// Start screen shake of given `intensity` in world pixels, decaying by `decay` pixels per second.
+    /// Stronger ongoing shake is not weakened.
+    pub fn shake(&mut self, intensity: f32, decay: f32) {
+        if intensity >= self.shake {
+            self.shake = intensity;
+            self.shake_decay = decay.max(0.0);
+        }
+    }
+
+    /// Check if the screen is shaking.
+    pub fn is_shaking(&self) -> bool {
+        self.shake > 0.0
+    }
+
+    /// Advance screen shake by `delta` time.
+    pub fn update(&mut self, delta: Duration) {
+        self.shake = (self.shake - self.shake_decay * delta.as_secs_f32()).max(0.0);
+        self.shake_offset = if self.shake > 0.0 {
+            Vector::new(self.random(), self.random()) * self.shake
+        } else {
+            Vector::new(0.0, 0.0)
+        };
+    }
+
+    /// Get world position of the top left corner of the view, including shake.
+    pub fn position(&self) -> Vector<f32> {
+        let size = self.view_size();
+        self.center - Vector::new(size.x() as f32 / 2.0, size.y() as f32 / 2.0) + self.shake_offset
+    }
+
+    /// Get offset for `Painter` drawing the world on a target of `view_size` dimensions.
+    pub fn offset(&self) -> Vector<i32> {
+        -self.position().map(f32::round).map(|value| value as i32)
+    }
+
+    /// Get transformation of world coordinates into screen coordinates, including zoom.
+    pub fn transform(&self) -> Affine {
+        let offset = self.offset();
+        Affine::translation(Vector::new(offset.x() as f32, offset.y() as f32))
+            .then(Affine::scale(self.zoom as f32, self.zoom as f32))
+    }
+
+    /// Convert world position into screen position.
+    pub fn world_to_screen<I: Into<Vector<f32>>>(&self, position: I) -> Vector<i32> {
+        let offset = self.offset();
+        let position = position.into().map(|value| value.floor() as i32);
+        (position + offset) * self.zoom
+    }
+
+    /// Convert screen position, e.g. `Mouse::position`, into world position.
+    pub fn screen_to_world<I: Into<Vector<i32>>>(&self, position: I) -> Vector<f32> {
+        let world = position.into().map(|value| value.div_euclid(self.zoom)) - self.offset();
+        world.map(|value| value as f32)
+    }
+
+    fn clamp(&mut self) {
+        let Some((from, to)) = self.bounds else {
+            return;
+        };
+        let size = self.view_size();
+        let half = Vector::new(size.x() as f32 / 2.0, size.y() as f32 / 2.0);
+        let axis = |center: f32, from: f32, to: f32, half: f32| {
+            if to - from <= half * 2.0 {
+                (from + to) / 2.0
+            } else {
+                center.clamp(from + half, to - half)
+            }
+        };
+        self.center = Vector::new(
+            axis(self.center.x(), from.x(), to.x(), half.x()),
+            axis(self.center.y(), from.y(), to.y(), half.y()),
+        );
+    }
+
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
@@ -2,7 +2,7 @@ use std::slice::Iter;
 
 use devotee_backend::BackendImage;
 
-use super::{Image, PixelsIterator};
+use super::{Image, ImageMut, PixelsIterator};
 use crate::util::vector::Vector;
 
 /// Canvas based on box slice of pixel data.
@@ -27,6 +27,20 @@ where
             height,
         }
     }
+
+    /// Change canvas resolution.
+    /// Overlapping pixels are kept, the new ones are set to `color`.
+    pub fn resize(&mut self, width: usize, height: usize, color: P) {
+        let mut data = vec![color; width * height].into_boxed_slice();
+        for y in 0..self.height.min(height) {
+            let columns = self.width.min(width);
+            data[y * width..y * width + columns]
+                .clone_from_slice(&self.data[y * self.width..y * self.width + columns]);
+        }
+        self.data = data;
+        self.width = width;
+        self.height = height;
+    }
 }
 
 impl<P> Image for Canvas<P>
@@ -35,7 +49,6 @@ where
 {
     type Pixel = P;
     type PixelRef<'a> = &'a P where P:'a;
-    type PixelMut<'a> = &'a mut P where P:'a;
     fn pixel(&self, position: Vector<i32>) -> Option<&P> {
         if position.x() < 0 || position.y() < 0 {
             return None;
@@ -48,6 +61,28 @@ where
         }
     }
 
+    /// Get reference to pixel.
+    /// # Safety
+    /// - `position` must be in range `[0, width-1]` by `x` and `[0, height-1]` by `y`.
+    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> &P {
+        let (x, y) = (position.x() as usize, position.y() as usize);
+        &self.data[x + self.width * y]
+    }
+
+    fn width(&self) -> i32 {
+        self.width as i32
+    }
+
+    fn height(&self) -> i32 {
+        self.height as i32
+    }
+}
+
+impl<P> ImageMut for Canvas<P>
+where
+    P: Clone,
+{
+    type PixelMut<'a> = &'a mut P where P:'a;
     fn pixel_mut(&mut self, position: Vector<i32>) -> Option<&mut P> {
         if position.x() < 0 || position.y() < 0 {
             return None;
@@ -60,14 +95,6 @@ where
         }
     }
 
-    /// Get reference to pixel.
-    /// # Safety
-    /// - `position` must be in range `[0, width-1]` by `x` and `[0, height-1]` by `y`.
-    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> &P {
-        let (x, y) = (position.x() as usize, position.y() as usize);
-        &self.data[x + self.width * y]
-    }
-
     /// Get mutable reference to pixel.
     /// # Safety
     /// - `position` must be in range `[0, width-1]` by `x` and `[0, height-1]` by `y`.
@@ -76,14 +103,6 @@ where
         &mut self.data[x + self.width * y]
     }
 
-    fn width(&self) -> i32 {
-        self.width as i32
-    }
-
-    fn height(&self) -> i32 {
-        self.height as i32
-    }
-
     fn clear(&mut self, color: P) {
         self.data = vec![color; self.width * self.height].into_boxed_slice();
     }
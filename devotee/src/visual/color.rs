@@ -1,3 +1,9 @@
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
 pub use devotee_backend::Converter;
 
 /// General color trait for color mixing.
@@ -6,3 +12,606 @@ pub trait Color {
     /// The `other` is applied on top of `self`.
     fn mix(self, other: Self) -> Self;
 }
+
+/// Error of palette loading.
+#[derive(Debug)]
+pub enum PaletteError {
+    /// File can't be read.
+    Io(std::io::Error),
+    /// Line can't be parsed as a color, the value is the line number starting from one.
+    Malformed(usize),
+}
+
+impl Display for PaletteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(error) => write!(f, "failed to read file: {}", error),
+            PaletteError::Malformed(line) => write!(f, "malformed color on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PaletteError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PaletteError {
+    fn from(error: std::io::Error) -> Self {
+        PaletteError::Io(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Cycle {
+    range: Range<usize>,
+    step: Duration,
+    elapsed: Duration,
+}
+
+impl Cycle {
+    fn shift(&self) -> usize {
+        let steps = self.elapsed.as_nanos() / self.step.as_nanos().max(1);
+        (steps % self.range.len() as u128) as usize
+    }
+}
+
+/// Indexed color palette of `0xRRGGBB` entries.
+/// It converts pixels of index type `P` with entry remapping, color cycling and fading applied.
+#[derive(Clone, Debug)]
+pub struct Palette<P> {
+    colors: Vec<u32>,
+    remap: Vec<usize>,
+    cycles: Vec<Cycle>,
+    fade_color: u32,
+    fade: f32,
+    fade_target: f32,
+    fade_speed: f32,
+    _index: PhantomData<fn(P)>,
+}
+
+impl<P> Palette<P> {
+    /// Create new palette with given `0xRRGGBB` colors.
+    pub fn new(colors: Vec<u32>) -> Self {
+        let remap = (0..colors.len()).collect();
+        Self {
+            colors,
+            remap,
+            cycles: Vec::new(),
+            fade_color: 0,
+            fade: 0.0,
+            fade_target: 0.0,
+            fade_speed: 0.0,
+            _index: PhantomData,
+        }
+    }
+
+    /// Load palette from .hex, .gpl (GIMP) or .pal (JASC) file.
+    pub fn load<A: AsRef<Path>>(path: A) -> Result<Self, PaletteError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parse palette in .hex, .gpl (GIMP) or .pal (JASC) format.
+    /// The format is detected by its header.
+    pub fn parse(text: &str) -> Result<Self, PaletteError> {
+        let lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()));
+        let first = text.lines().next().unwrap_or_default().trim();
+        let mut colors = Vec::new();
+        if first == "GIMP Palette" {
+            for (number, line) in lines.skip(1) {
+                let is_header = line.starts_with("Name:") || line.starts_with("Columns:");
+                if line.is_empty() || line.starts_with('#') || is_header {
+                    continue;
+                }
+                colors.push(parse_channels(line).ok_or(PaletteError::Malformed(number))?);
+            }
+        } else if first == "JASC-PAL" {
+            let mut lines = lines.skip(1).filter(|(_, line)| !line.is_empty());
+            lines.next().ok_or(PaletteError::Malformed(2))?;
+            let (number, count) = lines.next().ok_or(PaletteError::Malformed(3))?;
+            let count: usize = count.parse().map_err(|_| PaletteError::Malformed(number))?;
+            for (number, line) in lines.take(count) {
+                colors.push(parse_channels(line).ok_or(PaletteError::Malformed(number))?);
+            }
+        } else {
+            for (number, line) in lines {
+                if line.is_empty() || line.starts_with(';') {
+                    continue;
+                }
+                let hex = line.trim_start_matches('#');
+                let color = (hex.len() == 6)
+                    .then(|| u32::from_str_radix(hex, 16).ok())
+                    .flatten()
+                    .ok_or(PaletteError::Malformed(number))?;
+                colors.push(color);
+            }
+        }
+        Ok(Self::new(colors))
+    }
+
+    /// Get count of entries.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Check if the palette has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Get base colors of all entries.
+    pub fn colors(&self) -> &[u32] {
+        &self.colors
+    }
+
+    /// Get base color of the entry.
+    pub fn color(&self, index: usize) -> Option<u32> {
+        self.colors.get(index).copied()
+    }
+
+    /// Set base color of the entry.
+    /// Returns `false` if the index is out of the palette.
+    pub fn set_color(&mut self, index: usize, color: u32) -> bool {
+        let Some(entry) = self.colors.get_mut(index) else {
+            return false;
+        };
+        *entry = color;
+        true
+    }
+
+    /// Get entry used to display pixels of given `index`.
+    pub fn mapped(&self, index: usize) -> Option<usize> {
+        self.remap.get(index).copied()
+    }
+
+    /// Display pixels of given `index` with the `target` entry.
+    /// Returns `false` if any of indices is out of the palette.
+    pub fn remap(&mut self, index: usize, target: usize) -> bool {
+        if target >= self.colors.len() {
+            return false;
+        }
+        let Some(entry) = self.remap.get_mut(index) else {
+            return false;
+        };
+        *entry = target;
+        true
+    }
+
+    /// Display every entry with itself.
+    pub fn reset_remap(&mut self) {
+        self.remap = (0..self.colors.len()).collect();
+    }
+
+    /// Rotate colors of entries in `range` by one entry each `step`.
+    /// Returns `false` if the range is empty or out of the palette.
+    pub fn add_cycle(&mut self, range: Range<usize>, step: Duration) -> bool {
+        if range.is_empty() || range.end > self.colors.len() {
+            return false;
+        }
+        self.cycles.push(Cycle {
+            range,
+            step,
+            elapsed: Duration::ZERO,
+        });
+        true
+    }
+
+    /// Remove all color cycles.
+    pub fn clear_cycles(&mut self) {
+        self.cycles.clear();
+    }
+
+    /// Get current fade amount, zero means no fading and one means full fade color.
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    /// Get fade color.
+    pub fn fade_color(&self) -> u32 {
+        self.fade_color
+    }
+
+    /// Set fade to the `color` by the `amount` immediately, stopping any ongoing fading.
+    pub fn set_fade(&mut self, color: u32, amount: f32) {
+        self.fade_color = color;
+        self.fade = amount.clamp(0.0, 1.0);
+        self.fade_target = self.fade;
+    }
+
+    /// Change fade amount to `target` gradually over `duration` as the palette gets updated.
+    pub fn fade_to(&mut self, target: f32, duration: Duration) {
+        self.fade_target = target.clamp(0.0, 1.0);
+        self.fade_speed = if duration.is_zero() {
+            f32::INFINITY
+        } else {
+            (self.fade_target - self.fade).abs() / duration.as_secs_f32()
+        };
+    }
+
+    /// Check if fade amount is changing.
+    pub fn is_fading(&self) -> bool {
+        self.fade != self.fade_target
+    }
+
+    /// Advance color cycles and fading by `delta` time.
+    pub fn update(&mut self, delta: Duration) {
+        for cycle in self.cycles.iter_mut() {
+            cycle.elapsed += delta;
+        }
+        let change = self.fade_speed * delta.as_secs_f32();
+        self.fade = if self.fade < self.fade_target {
+            (self.fade + change).min(self.fade_target)
+        } else {
+            (self.fade - change).max(self.fade_target)
+        };
+    }
+
+    /// Get displayed color of pixels with given `index`.
+    pub fn resolve(&self, index: usize) -> Option<u32> {
+        let mut entry = self.mapped(index)?;
+        for cycle in self.cycles.iter().rev() {
+            if cycle.range.contains(&entry) {
+                let length = cycle.range.len();
+                let offset = entry - cycle.range.start;
+                entry = cycle.range.start + (offset + length - cycle.shift()) % length;
+                break;
+            }
+        }
+        let color = self.colors[entry];
+        if self.fade <= 0.0 {
+            return Some(color);
+        }
+        let channel = |shift: u32| {
+            let from = ((color >> shift) & 0xff) as f32;
+            let to = ((self.fade_color >> shift) & 0xff) as f32;
+            ((from + (to - from) * self.fade).round() as u32) << shift
+        };
+        Some(channel(16) | channel(8) | channel(0))
+    }
+}
+
+impl<P> Converter for Palette<P>
+where
+    P: Clone + Into<usize>,
+{
+    type Palette = P;
+
+    fn convert(&self, color: &Self::Palette) -> u32 {
+        self.resolve(color.clone().into()).unwrap_or_default()
+    }
+}
+
+/// Separable blend mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Source replaces the backdrop.
+    #[default]
+    Normal,
+    /// Channels are summed, brightening the backdrop.
+    Add,
+    /// Channels are multiplied, darkening the backdrop.
+    Multiply,
+    /// Inverted channels are multiplied, brightening the backdrop.
+    Screen,
+    /// Multiply for dark backdrop and screen for bright one.
+    Overlay,
+}
+
+impl BlendMode {
+    /// Blend normalized `backdrop` and `source` channel values.
+    pub fn apply(self, backdrop: f32, source: f32) -> f32 {
+        let screen = |a: f32, b: f32| a + b - a * b;
+        match self {
+            BlendMode::Normal => source,
+            BlendMode::Add => (backdrop + source).min(1.0),
+            BlendMode::Multiply => backdrop * source,
+            BlendMode::Screen => screen(backdrop, source),
+            BlendMode::Overlay => {
+                if backdrop <= 0.5 {
+                    2.0 * backdrop * source
+                } else {
+                    screen(2.0 * backdrop - 1.0, source)
+                }
+            }
+        }
+    }
+}
+
+/// Color supporting blend modes.
+pub trait Blend {
+    /// Blend `other` on top of `self` with the `mode`.
+    fn blend(self, other: Self, mode: BlendMode) -> Self;
+}
+
+/// True color pixel with straight alpha, 8 bits per channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgba8 {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+impl Rgba8 {
+    /// Create new color from channels.
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create new opaque color from channels.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 0xff)
+    }
+
+    /// Create new color from `0xRRGGBBAA` value.
+    pub const fn from_rgba(value: u32) -> Self {
+        let [r, g, b, a] = value.to_be_bytes();
+        Self::new(r, g, b, a)
+    }
+
+    /// Get `0xRRGGBBAA` value.
+    pub const fn rgba(&self) -> u32 {
+        u32::from_be_bytes([self.r, self.g, self.b, self.a])
+    }
+
+    /// Get red channel.
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    /// Get green channel.
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    /// Get blue channel.
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    /// Get alpha channel.
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+
+    /// Get new color with desired alpha.
+    pub fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Get color with channels multiplied by alpha.
+    pub fn premultiply(self) -> PremultipliedRgba8 {
+        let channel = |value: u8| ((value as u16 * self.a as u16 + 127) / 255) as u8;
+        PremultipliedRgba8 {
+            r: channel(self.r),
+            g: channel(self.g),
+            b: channel(self.b),
+            a: self.a,
+        }
+    }
+
+    fn normalized(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|value| value as f32 / 255.0)
+    }
+
+    fn from_normalized(channels: [f32; 4]) -> Self {
+        let [r, g, b, a] = channels.map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Self::new(r, g, b, a)
+    }
+}
+
+impl Color for Rgba8 {
+    fn mix(self, other: Self) -> Self {
+        self.blend(other, BlendMode::Normal)
+    }
+}
+
+impl Blend for Rgba8 {
+    fn blend(self, other: Self, mode: BlendMode) -> Self {
+        let backdrop = self.normalized();
+        let source = other.normalized();
+        let (backdrop_alpha, source_alpha) = (backdrop[3], source[3]);
+        let alpha = source_alpha + backdrop_alpha * (1.0 - source_alpha);
+        if alpha <= 0.0 {
+            return Self::default();
+        }
+        let channel = |index: usize| {
+            let (backdrop, source) = (backdrop[index], source[index]);
+            let blended = mode.apply(backdrop, source);
+            (source_alpha * (1.0 - backdrop_alpha) * source
+                + source_alpha * backdrop_alpha * blended
+                + (1.0 - source_alpha) * backdrop_alpha * backdrop)
+                / alpha
+        };
+        Self::from_normalized([channel(0), channel(1), channel(2), alpha])
+    }
+}
+
+impl From<Rgba8> for u32 {
+    fn from(value: Rgba8) -> Self {
+        value.rgba() >> 8
+    }
+}
+
+/// True color pixel with channels multiplied by alpha, 8 bits per channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PremultipliedRgba8 {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+impl PremultipliedRgba8 {
+    /// Get color with straight alpha.
+    pub fn unpremultiply(self) -> Rgba8 {
+        if self.a == 0 {
+            return Rgba8::default();
+        }
+        let channel =
+            |value: u8| ((value as u16 * 255 + self.a as u16 / 2) / self.a as u16).min(255) as u8;
+        Rgba8::new(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+
+    /// Get alpha channel.
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+}
+
+impl Color for PremultipliedRgba8 {
+    fn mix(self, other: Self) -> Self {
+        let inverse = 255 - other.a as u16;
+        let channel = |backdrop: u8, source: u8| {
+            (source as u16 + (backdrop as u16 * inverse + 127) / 255).min(255) as u8
+        };
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: channel(self.a, other.a),
+        }
+    }
+}
+
+impl Blend for PremultipliedRgba8 {
+    fn blend(self, other: Self, mode: BlendMode) -> Self {
+        self.unpremultiply()
+            .blend(other.unpremultiply(), mode)
+            .premultiply()
+    }
+}
+
+impl From<PremultipliedRgba8> for u32 {
+    fn from(value: PremultipliedRgba8) -> Self {
+        u32::from_be_bytes([0, value.r, value.g, value.b])
+    }
+}
+
+/// Opaque true color pixel packed into 16 bits, 5 bits of red, 6 of green and 5 of blue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb565(u16);
+
+impl Rgb565 {
+    /// Create new color from 8 bit channels, dropping lower bits.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self(((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3))
+    }
+
+    /// Create new color from packed bits.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Get packed bits.
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Get red channel expanded to 8 bits.
+    pub fn r(&self) -> u8 {
+        let value = (self.0 >> 11) as u8 & 0x1f;
+        (value << 3) | (value >> 2)
+    }
+
+    /// Get green channel expanded to 8 bits.
+    pub fn g(&self) -> u8 {
+        let value = (self.0 >> 5) as u8 & 0x3f;
+        (value << 2) | (value >> 4)
+    }
+
+    /// Get blue channel expanded to 8 bits.
+    pub fn b(&self) -> u8 {
+        let value = self.0 as u8 & 0x1f;
+        (value << 3) | (value >> 2)
+    }
+}
+
+impl Color for Rgb565 {
+    fn mix(self, other: Self) -> Self {
+        other
+    }
+}
+
+impl Blend for Rgb565 {
+    fn blend(self, other: Self, mode: BlendMode) -> Self {
+        let channel = |backdrop: u8, source: u8| {
+            let value = mode.apply(backdrop as f32 / 255.0, source as f32 / 255.0);
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        Self::new(
+            channel(self.r(), other.r()),
+            channel(self.g(), other.g()),
+            channel(self.b(), other.b()),
+        )
+    }
+}
+
+impl From<Rgb565> for u32 {
+    fn from(value: Rgb565) -> Self {
+        u32::from_be_bytes([0, value.r(), value.g(), value.b()])
+    }
+}
+
+impl From<Rgba8> for Rgb565 {
+    fn from(value: Rgba8) -> Self {
+        Self::new(value.r, value.g, value.b)
+    }
+}
+
+impl From<Rgb565> for Rgba8 {
+    fn from(value: Rgb565) -> Self {
+        Self::rgb(value.r(), value.g(), value.b())
+    }
+}
+
+/// Converter passing `0xRRGGBB` value of true color pixels as is, alpha is ignored.
+#[derive(Clone, Copy, Debug)]
+pub struct Identity<P> {
+    _pixel: PhantomData<fn(P)>,
+}
+
+impl<P> Identity<P> {
+    /// Create new identity converter.
+    pub fn new() -> Self {
+        Self {
+            _pixel: PhantomData,
+        }
+    }
+}
+
+impl<P> Default for Identity<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Converter for Identity<P>
+where
+    P: Clone + Into<u32>,
+{
+    type Palette = P;
+
+    fn convert(&self, color: &Self::Palette) -> u32 {
+        color.clone().into() & 0x00ff_ffff
+    }
+}
+
+fn parse_channels(line: &str) -> Option<u32> {
+    let mut channels = line
+        .split_whitespace()
+        .map(|value| value.parse::<u8>().ok());
+    let mut color = 0;
+    for _ in 0..3 {
+        color = (color << 8) | channels.next()?? as u32;
+    }
+    Some(color)
+}
@@ -0,0 +1,221 @@
+use std::ops::Deref;
+
+use super::canvas::Canvas;
+use super::color::Converter;
+use super::{Image, ImageMut};
+use crate::util::vector::Vector;
+
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Get ordered dithering threshold in `(0, 1)` range for the pixel position.
+/// It repeats every 8 pixels in both directions.
+pub fn bayer(x: i32, y: i32) -> f32 {
+    (BAYER[(y & 7) as usize][(x & 7) as usize] as f32 + 0.5) / 64.0
+}
+
+/// Helper ordered dithering mapper.
+/// It picks `high` where `level` for the pixel position exceeds the Bayer threshold and `low` otherwise.
+pub fn ordered<P, L>(level: L, low: P, high: P) -> impl FnMut(i32, i32, P) -> P
+where
+    P: Clone,
+    L: FnMut(i32, i32) -> f32,
+{
+    let mut level = level;
+    move |x, y, _| {
+        if bayer(x, y) < level(x, y) {
+            high.clone()
+        } else {
+            low.clone()
+        }
+    }
+}
+
+/// Fill pattern of 8 by 8 pixels repeating over the target.
+/// Each row is a byte with the leftmost pixel in the most significant bit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    rows: [u8; 8],
+}
+
+impl Pattern {
+    /// Create new pattern from its rows, top to bottom.
+    pub fn new(rows: [u8; 8]) -> Self {
+        Self { rows }
+    }
+
+    /// Create new pattern from bits, the top row is in the most significant byte.
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            rows: bits.to_be_bytes(),
+        }
+    }
+
+    /// Create new ordered dithering pattern with `level` share of set pixels.
+    pub fn bayer(level: f32) -> Self {
+        let mut rows = [0; 8];
+        for (y, row) in rows.iter_mut().enumerate() {
+            for x in 0..8 {
+                if bayer(x, y as i32) < level {
+                    *row |= 0x80 >> x;
+                }
+            }
+        }
+        Self { rows }
+    }
+
+    /// Get pattern rows.
+    pub fn rows(&self) -> [u8; 8] {
+        self.rows
+    }
+
+    /// Get pattern bits, the top row is in the most significant byte.
+    pub fn bits(&self) -> u64 {
+        u64::from_be_bytes(self.rows)
+    }
+
+    /// Get new pattern with set and unset pixels swapped.
+    pub fn inverted(self) -> Self {
+        Self {
+            rows: self.rows.map(|row| !row),
+        }
+    }
+
+    /// Check if the pattern is set at the position.
+    pub fn is_set(&self, x: i32, y: i32) -> bool {
+        self.rows[(y & 7) as usize] & (0x80 >> (x & 7)) != 0
+    }
+
+    /// Get mapper painting `set` and `unset` values by the pattern.
+    pub fn paint<P: Clone>(self, set: P, unset: P) -> impl FnMut(i32, i32, P) -> P {
+        move |x, y, _| {
+            if self.is_set(x, y) {
+                set.clone()
+            } else {
+                unset.clone()
+            }
+        }
+    }
+
+    /// Get mapper painting `set` value by the pattern and keeping the original elsewhere.
+    pub fn draw<P: Clone>(self, set: P) -> impl FnMut(i32, i32, P) -> P {
+        move |x, y, pixel| {
+            if self.is_set(x, y) {
+                set.clone()
+            } else {
+                pixel
+            }
+        }
+    }
+}
+
+/// Error diffusion kernel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Diffusion {
+    /// Floyd-Steinberg kernel, the whole error is spread to four neighbors.
+    #[default]
+    FloydSteinberg,
+    /// Atkinson kernel, only three quarters of the error are spread, keeping higher contrast.
+    Atkinson,
+}
+
+impl Diffusion {
+    fn kernel(self) -> &'static [(i32, i32, f32)] {
+        match self {
+            Diffusion::FloydSteinberg => &[
+                (1, 0, 7.0 / 16.0),
+                (-1, 1, 3.0 / 16.0),
+                (0, 1, 5.0 / 16.0),
+                (1, 1, 1.0 / 16.0),
+            ],
+            Diffusion::Atkinson => &[
+                (1, 0, 0.125),
+                (2, 0, 0.125),
+                (-1, 1, 0.125),
+                (0, 1, 0.125),
+                (1, 1, 0.125),
+                (0, 2, 0.125),
+            ],
+        }
+    }
+}
+
+fn channels(color: u32) -> [f32; 3] {
+    [
+        ((color >> 16) & 0xff) as f32,
+        ((color >> 8) & 0xff) as f32,
+        (color & 0xff) as f32,
+    ]
+}
+
+/// Quantize true color `source` of `0xRRGGBB` pixels into the `palette` with error diffusion.
+/// Palette colors are resolved with the `converter`.
+/// Returns `None` if the palette is empty.
+pub fn diffuse<'a, U, P, C>(
+    source: &'a U,
+    palette: &[P],
+    converter: &C,
+    diffusion: Diffusion,
+) -> Option<Canvas<P>>
+where
+    U: Image<Pixel = u32> + ?Sized,
+    U::PixelRef<'a>: Deref<Target = u32>,
+    P: Clone,
+    C: Converter<Palette = P>,
+{
+    let first = palette.first()?;
+    let colors: Vec<_> = palette
+        .iter()
+        .map(|color| channels(converter.convert(color)))
+        .collect();
+    let (width, height) = (source.width().max(0), source.height().max(0));
+    let mut result = Canvas::with_resolution(first.clone(), width as usize, height as usize);
+    let mut errors = vec![[0.0; 3]; (width * height) as usize];
+
+    for y in 0..height {
+        for x in 0..width {
+            let position = Vector::new(x, y);
+            let Some(pixel) = source.pixel(position) else {
+                continue;
+            };
+            let index = (x + y * width) as usize;
+            let mut color = channels(*pixel);
+            for (channel, error) in color.iter_mut().zip(errors[index]) {
+                *channel = (*channel + error).clamp(0.0, 255.0);
+            }
+
+            let distance = |other: &[f32; 3]| {
+                (0..3)
+                    .map(|channel| (color[channel] - other[channel]).powi(2))
+                    .sum::<f32>()
+            };
+            let (nearest, chosen) = colors
+                .iter()
+                .enumerate()
+                .min_by(|a, b| distance(a.1).total_cmp(&distance(b.1)))?;
+            if let Some(target) = result.pixel_mut(position) {
+                *target = palette[nearest].clone();
+            }
+
+            for &(dx, dy, weight) in diffusion.kernel() {
+                let (x, y) = (x + dx, y + dy);
+                if x < 0 || x >= width || y >= height {
+                    continue;
+                }
+                let error = &mut errors[(x + y * width) as usize];
+                for channel in 0..3 {
+                    error[channel] += (color[channel] - chosen[channel]) * weight;
+                }
+            }
+        }
+    }
+    Some(result)
+}
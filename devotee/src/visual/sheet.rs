@@ -0,0 +1,94 @@
+use std::ops::{Deref, DerefMut};
+
+use super::view::View;
+use super::{Blit, Flip, Image, ImageMut, Painter};
+use crate::util::vector::Vector;
+
+/// Image sliced into frames.
+#[derive(Clone, Debug)]
+pub struct SpriteSheet<I> {
+    image: I,
+    frames: Vec<(Vector<i32>, Vector<i32>)>,
+}
+
+impl<I> SpriteSheet<I>
+where
+    I: Image,
+{
+    /// Create new sprite sheet by slicing the `image` into a grid of `frame_size` cells.
+    /// Frames are enumerated left to right, top to bottom.
+    pub fn with_grid<S: Into<Vector<i32>>>(image: I, frame_size: S) -> Self {
+        let frame_size = frame_size.into();
+        let mut frames = Vec::new();
+        if frame_size.x() > 0 && frame_size.y() > 0 {
+            for y in 0..image.height() / frame_size.y() {
+                for x in 0..image.width() / frame_size.x() {
+                    let from = Vector::new(x * frame_size.x(), y * frame_size.y());
+                    frames.push((from, from + frame_size));
+                }
+            }
+        }
+        Self { image, frames }
+    }
+
+    /// Create new sprite sheet with explicitly defined frames in `[from, to)` ranges.
+    pub fn with_frames(image: I, frames: Vec<(Vector<i32>, Vector<i32>)>) -> Self {
+        Self { image, frames }
+    }
+
+    /// Get reference to the underlying image.
+    pub fn image(&self) -> &I {
+        &self.image
+    }
+
+    /// Get count of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if there are no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get bounds of specific frame.
+    pub fn bounds(&self, index: usize) -> Option<(Vector<i32>, Vector<i32>)> {
+        self.frames.get(index).copied()
+    }
+
+    /// Get view of specific frame.
+    pub fn frame(&self, index: usize) -> Option<View<'_, I>> {
+        self.frames
+            .get(index)
+            .map(|(from, to)| View::new(&self.image, *from, *to))
+    }
+
+    /// Draw specific frame with provided painter and function.
+    /// The function receives pixel coordinates in the sheet image space.
+    /// Returns `false` if there is no such frame.
+    pub fn draw<'b, T, A, F, P>(
+        &'b self,
+        painter: &mut Painter<T>,
+        at: A,
+        index: usize,
+        flip: Flip,
+        function: F,
+    ) -> bool
+    where
+        T: ImageMut<Pixel = P>,
+        P: Clone,
+        A: Into<Vector<i32>>,
+        I::Pixel: Clone,
+        I::PixelRef<'b>: Deref<Target = I::Pixel>,
+        F: FnMut(i32, i32, P, i32, i32, I::Pixel) -> P,
+        for<'c> T::PixelRef<'c>: Deref<Target = P>,
+        for<'c> T::PixelMut<'c>: DerefMut<Target = P>,
+    {
+        let Some((from, to)) = self.bounds(index) else {
+            return false;
+        };
+        let blit = Blit::new().with_source(from, to).with_flip(flip);
+        painter.blit(at, &self.image, blit, function);
+        true
+    }
+}
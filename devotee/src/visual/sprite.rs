@@ -1,6 +1,6 @@
 use devotee_backend::BackendImage;
 
-use super::{Image, PixelsIterator};
+use super::{Image, ImageMut, PixelsIterator};
 use crate::util::vector::Vector;
 
 /// Sprite of fixed dimensions.
@@ -31,7 +31,6 @@ where
 {
     type Pixel = P;
     type PixelRef<'a> = &'a P where P:'a;
-    type PixelMut<'a> = &'a mut P where P:'a;
     fn pixel(&self, position: Vector<i32>) -> Option<&P> {
         if position.x() < 0 || position.y() < 0 {
             return None;
@@ -44,6 +43,25 @@ where
         }
     }
 
+    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> &P {
+        let (x, y) = (position.x() as usize, position.y() as usize);
+        &self.data[y][x]
+    }
+
+    fn width(&self) -> i32 {
+        W as i32
+    }
+
+    fn height(&self) -> i32 {
+        H as i32
+    }
+}
+
+impl<P, const W: usize, const H: usize> ImageMut for Sprite<P, W, H>
+where
+    P: Copy,
+{
+    type PixelMut<'a> = &'a mut P where P:'a;
     fn pixel_mut(&mut self, position: Vector<i32>) -> Option<&mut P> {
         if position.x() < 0 || position.y() < 0 {
             return None;
@@ -56,24 +74,11 @@ where
         }
     }
 
-    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> &P {
-        let (x, y) = (position.x() as usize, position.y() as usize);
-        &self.data[y][x]
-    }
-
     unsafe fn unsafe_pixel_mut(&mut self, position: Vector<i32>) -> &mut P {
         let (x, y) = (position.x() as usize, position.y() as usize);
         &mut self.data[y][x]
     }
 
-    fn width(&self) -> i32 {
-        W as i32
-    }
-
-    fn height(&self) -> i32 {
-        H as i32
-    }
-
     fn clear(&mut self, color: P) {
         self.data = [[color; W]; H];
     }
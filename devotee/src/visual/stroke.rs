@@ -0,0 +1,439 @@
+use crate::util::vector::Vector;
+
+const MITER_LIMIT: f32 = 4.0;
+
+/// Shape of stroke ends.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Cap {
+    /// Stroke ends exactly at its end points.
+    #[default]
+    Butt,
+    /// Stroke is extended by half of its width past its end points.
+    Square,
+    /// Stroke ends are rounded with half of its width radius.
+    Round,
+}
+
+/// Shape of stroke corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Join {
+    /// Outer edges are extended until they meet.
+    /// Too sharp corners are beveled.
+    #[default]
+    Miter,
+    /// Outer edges are connected with a straight line.
+    Bevel,
+    /// Corners are rounded with half of stroke width radius.
+    Round,
+}
+
+/// Outline drawing options.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Stroke {
+    width: i32,
+    cap: Cap,
+    join: Join,
+    dash: Vec<u32>,
+    phase: i32,
+}
+
+impl Stroke {
+    /// Create new solid stroke of given `width` in pixels.
+    pub fn new(width: i32) -> Self {
+        Self {
+            width: width.max(1),
+            cap: Cap::Butt,
+            join: Join::Miter,
+            dash: Vec::new(),
+            phase: 0,
+        }
+    }
+
+    /// Get new stroke with desired ends shape.
+    pub fn with_cap(self, cap: Cap) -> Self {
+        Self { cap, ..self }
+    }
+
+    /// Get new stroke with desired corners shape.
+    pub fn with_join(self, join: Join) -> Self {
+        Self { join, ..self }
+    }
+
+    /// Get new stroke with dash pattern of alternating on and off lengths in pixels.
+    /// Empty pattern or pattern without nonzero lengths means solid stroke.
+    pub fn with_dash(self, pattern: &[u32]) -> Self {
+        let dash = if pattern.iter().any(|length| *length > 0) {
+            pattern.to_vec()
+        } else {
+            Vec::new()
+        };
+        Self { dash, ..self }
+    }
+
+    /// Get new stroke with dash pattern shifted by `phase` pixels.
+    /// Changing phase over time makes the dashes march.
+    pub fn with_phase(self, phase: i32) -> Self {
+        Self { phase, ..self }
+    }
+
+    /// Get stroke width.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Get ends shape.
+    pub fn cap(&self) -> Cap {
+        self.cap
+    }
+
+    /// Get corners shape.
+    pub fn join(&self) -> Join {
+        self.join
+    }
+
+    /// Get dash pattern.
+    pub fn dash(&self) -> &[u32] {
+        &self.dash
+    }
+
+    /// Get dash pattern phase.
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    fn half(&self) -> f32 {
+        self.width as f32 / 2.0
+    }
+
+    fn is_on(&self, position: f32) -> bool {
+        if self.dash.is_empty() {
+            return true;
+        }
+        // Dash is never all zeros, see `with_dash`, so the period is positive.
+        let period = self.dash.iter().map(|length| *length as u64).sum::<u64>() as f32
+            * (1 + self.dash.len() % 2) as f32;
+        let mut position = (position.floor() + self.phase as f32).rem_euclid(period);
+        for (index, length) in self.dash.iter().cycle().enumerate() {
+            if position < *length as f32 {
+                return index % 2 == 0;
+            }
+            position -= *length as f32;
+        }
+        true
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// Set of pixels covered by a shape.
+pub(super) struct Mask {
+    from: Vector<i32>,
+    to: Vector<i32>,
+    marks: Vec<bool>,
+}
+
+impl Mask {
+    fn new(from: Vector<i32>, to: Vector<i32>) -> Self {
+        let size = to - from;
+        let marks = vec![false; (size.x().max(0) * size.y().max(0)) as usize];
+        Self { from, to, marks }
+    }
+
+    fn mark_where<F: FnMut(Vector<f32>) -> bool>(
+        &mut self,
+        from: Vector<f32>,
+        to: Vector<f32>,
+        mut predicate: F,
+    ) {
+        let start_x = (from.x().floor() as i32).max(self.from.x());
+        let start_y = (from.y().floor() as i32).max(self.from.y());
+        let end_x = (to.x().ceil() as i32 + 1).min(self.to.x());
+        let end_y = (to.y().ceil() as i32 + 1).min(self.to.y());
+        let width = self.to.x() - self.from.x();
+        for y in start_y..end_y {
+            for x in start_x..end_x {
+                let index = (x - self.from.x() + (y - self.from.y()) * width) as usize;
+                if !self.marks[index] && predicate(Vector::new(x as f32, y as f32)) {
+                    self.marks[index] = true;
+                }
+            }
+        }
+    }
+
+    /// Get positions of all marked pixels.
+    pub(super) fn positions(&self) -> impl Iterator<Item = Vector<i32>> + '_ {
+        let width = self.to.x() - self.from.x();
+        self.marks
+            .iter()
+            .enumerate()
+            .filter(|(_, marked)| **marked)
+            .map(move |(index, _)| self.from + (index as i32 % width, index as i32 / width))
+    }
+}
+
+struct Segment {
+    start: Vector<f32>,
+    direction: Vector<f32>,
+    normal: Vector<f32>,
+    length: f32,
+    major: f32,
+    steps: f32,
+    position: f32,
+}
+
+impl Segment {
+    fn new(from: Vector<i32>, to: Vector<i32>, position: f32) -> Self {
+        let start = float(from);
+        let delta = float(to) - start;
+        let length = delta.dot::<f32, f32>(delta).sqrt();
+        let steps = delta.x().abs().max(delta.y().abs());
+        let direction = if length > 0.0 {
+            delta / length
+        } else {
+            Vector::new(1.0, 0.0)
+        };
+        let normal = Vector::new(-direction.y(), direction.x());
+        let major = if delta.x().abs() >= delta.y().abs() {
+            direction.x().abs().max(f32::EPSILON)
+        } else {
+            direction.y().abs()
+        };
+        Self {
+            start,
+            direction,
+            normal,
+            length,
+            major,
+            steps,
+            position,
+        }
+    }
+
+    fn end(&self) -> Vector<f32> {
+        self.start + self.direction * self.length
+    }
+
+    fn along(&self, relative: Vector<f32>) -> f32 {
+        relative.dot(self.direction)
+    }
+}
+
+fn float(vector: Vector<i32>) -> Vector<f32> {
+    Vector::new(vector.x() as f32, vector.y() as f32)
+}
+
+fn cross(a: Vector<f32>, b: Vector<f32>) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+fn is_inside(point: Vector<f32>, polygon: &[Vector<f32>]) -> bool {
+    let mut sign = 0.0;
+    for (index, a) in polygon.iter().enumerate() {
+        let b = polygon[(index + 1) % polygon.len()];
+        let value = cross(b - *a, point - *a);
+        if value * sign < 0.0 {
+            return false;
+        }
+        if value != 0.0 {
+            sign = value;
+        }
+    }
+    true
+}
+
+fn bounds(points: &[Vector<f32>], margin: f32) -> (Vector<f32>, Vector<f32>) {
+    let (from, to) = points.iter().fold(
+        (points[0], points[0]),
+        |(from, to): (Vector<f32>, Vector<f32>), point| {
+            (
+                Vector::new(from.x().min(point.x()), from.y().min(point.y())),
+                Vector::new(to.x().max(point.x()), to.y().max(point.y())),
+            )
+        },
+    );
+    (from - (margin, margin), to + (margin, margin))
+}
+
+fn mark_disc(mask: &mut Mask, center: Vector<f32>, radius: f32) {
+    let margin = Vector::new(radius, radius);
+    mask.mark_where(center - margin, center + margin, |point| {
+        let relative = point - center;
+        relative.dot::<f32, f32>(relative) <= radius * radius
+    });
+}
+
+fn mark_polygon(mask: &mut Mask, polygon: &[Vector<f32>]) {
+    let (from, to) = bounds(polygon, 0.0);
+    mask.mark_where(from, to, |point| is_inside(point, polygon));
+}
+
+fn mark_segment(mask: &mut Mask, stroke: &Stroke, segment: &Segment, extension: (f32, f32)) {
+    let half = stroke.half();
+    let (from, to) = bounds(
+        &[segment.start, segment.end()],
+        half + extension.0.max(extension.1),
+    );
+    mask.mark_where(from, to, |point| {
+        let relative = point - segment.start;
+        let along = segment.along(relative);
+        if along < -extension.0 || along > segment.length + extension.1 {
+            return false;
+        }
+        let side = relative.dot::<f32, f32>(segment.normal) / segment.major;
+        // One pixel wide stroke keeps exactly one pixel per major axis step.
+        let limit = if stroke.width == 1 {
+            half
+        } else {
+            half / segment.major
+        };
+        if side < -limit || side >= limit {
+            return false;
+        }
+        let step = if segment.length > 0.0 {
+            along.clamp(0.0, segment.length) / segment.length * segment.steps
+        } else {
+            0.0
+        };
+        stroke.is_on(segment.position + step)
+    });
+}
+
+fn mark_join(mask: &mut Mask, stroke: &Stroke, incoming: &Segment, outgoing: &Segment) {
+    let half = stroke.half();
+    let vertex = outgoing.start;
+    if stroke.join == Join::Round {
+        mark_disc(mask, vertex, half);
+        return;
+    }
+    let turn = outgoing.direction.dot::<f32, f32>(incoming.normal);
+    let outer = if turn > 0.0 { -1.0 } else { 1.0 };
+    for side in [outer, -outer] {
+        let first = vertex + incoming.normal * (half * side);
+        let second = vertex + outgoing.normal * (half * side);
+        let sum = incoming.normal + outgoing.normal;
+        let cosine = sum.dot::<f32, f32>(sum).sqrt() / 2.0;
+        if side == outer && stroke.join == Join::Miter && cosine > 1.0 / MITER_LIMIT {
+            let tip = vertex + sum * (half * side / (2.0 * cosine * cosine));
+            mark_polygon(mask, &[vertex, first, tip, second]);
+        } else {
+            mark_polygon(mask, &[vertex, first, second]);
+        }
+    }
+}
+
+/// Rasterize polyline stroke within `[from, to)` bounds.
+pub(super) fn polyline(
+    stroke: &Stroke,
+    points: &[Vector<i32>],
+    closed: bool,
+    (from, to): (Vector<i32>, Vector<i32>),
+) -> Mask {
+    if points.is_empty() {
+        return Mask::new(from, from);
+    }
+    let half = stroke.half();
+    let floats: Vec<_> = points.iter().copied().map(float).collect();
+    let (start, end) = bounds(&floats, half * MITER_LIMIT + 1.0);
+    let from = Vector::new(
+        from.x().max(start.x() as i32),
+        from.y().max(start.y() as i32),
+    );
+    let to = Vector::new(
+        to.x().min(end.x() as i32 + 1),
+        to.y().min(end.y() as i32 + 1),
+    );
+    let mut mask = Mask::new(from, to);
+
+    let mut pairs: Vec<_> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed && points.len() > 2 {
+        pairs.push((points[points.len() - 1], points[0]));
+    }
+    if pairs.is_empty() {
+        pairs.push((points[0], points[0]));
+    }
+    let mut position = 0.0;
+    let segments: Vec<_> = pairs
+        .into_iter()
+        .map(|(a, b)| {
+            let segment = Segment::new(a, b, position);
+            position += segment.steps;
+            segment
+        })
+        .collect();
+
+    let last = segments.len() - 1;
+    let open = !closed || points.len() <= 2;
+    for (index, segment) in segments.iter().enumerate() {
+        let square = if open && stroke.cap == Cap::Square {
+            half
+        } else {
+            0.0
+        };
+        let extension = (
+            if index == 0 { square } else { 0.0 },
+            if index == last { square } else { 0.0 },
+        );
+        mark_segment(&mut mask, stroke, segment, extension);
+    }
+
+    for pair in segments.windows(2) {
+        if stroke.is_on(pair[1].position) {
+            mark_join(&mut mask, stroke, &pair[0], &pair[1]);
+        }
+    }
+    if open {
+        if stroke.cap == Cap::Round {
+            if stroke.is_on(0.0) {
+                mark_disc(&mut mask, segments[0].start, half);
+            }
+            if stroke.is_on(position) {
+                mark_disc(&mut mask, segments[last].end(), half);
+            }
+        }
+    } else if stroke.is_on(0.0) {
+        mark_join(&mut mask, stroke, &segments[last], &segments[0]);
+    }
+    mask
+}
+
+/// Rasterize circle stroke within `[from, to)` bounds.
+pub(super) fn circle(
+    stroke: &Stroke,
+    center: Vector<i32>,
+    radius: i32,
+    (from, to): (Vector<i32>, Vector<i32>),
+) -> Mask {
+    let half = stroke.half();
+    let outer = radius as f32 + half;
+    let inner = radius as f32 - half;
+    let margin = outer.ceil() as i32 + 1;
+    let from = Vector::new(
+        from.x().max(center.x() - margin),
+        from.y().max(center.y() - margin),
+    );
+    let to = Vector::new(
+        to.x().min(center.x() + margin + 1),
+        to.y().min(center.y() + margin + 1),
+    );
+    let mut mask = Mask::new(from, to);
+
+    let center = float(center);
+    let margin = Vector::new(outer, outer);
+    mask.mark_where(center - margin, center + margin, |point| {
+        let relative = point - center;
+        let distance = relative.dot::<f32, f32>(relative).sqrt();
+        if distance < inner || distance >= outer {
+            return false;
+        }
+        let angle = relative
+            .y()
+            .atan2(relative.x())
+            .rem_euclid(std::f32::consts::TAU);
+        stroke.is_on(angle * radius as f32)
+    });
+    mask
+}
@@ -0,0 +1,1234 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+use base64::Engine;
+use roxmltree::Node;
+use serde_json::Value;
+
+use super::tilemap::{Tile, Tilemap};
+use super::{Flip, Rotation};
+use crate::util::vector::Vector;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const GID_MASK: u32 = 0x0fff_ffff;
+// Upper bound of tiles in a single layer.
+const MAX_TILES: usize = 1 << 24;
+
+/// Error of Tiled map loading.
+#[derive(Debug)]
+pub enum Error {
+    /// File can't be read.
+    Io(std::io::Error),
+    /// JSON document is malformed.
+    Json(serde_json::Error),
+    /// XML document is malformed.
+    Xml(roxmltree::Error),
+    /// Document is well-formed but misses required data, the value describes missing data.
+    Malformed(&'static str),
+    /// Document uses unsupported feature, the value describes the feature.
+    Unsupported(&'static str),
+    /// Data exceeds supported size limits, the value describes the oversized data.
+    TooLarge(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "failed to read file: {}", error),
+            Error::Json(error) => write!(f, "malformed JSON: {}", error),
+            Error::Xml(error) => write!(f, "malformed XML: {}", error),
+            Error::Malformed(what) => write!(f, "malformed map: {}", what),
+            Error::Unsupported(what) => write!(f, "unsupported feature: {}", what),
+            Error::TooLarge(what) => write!(f, "too large: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Json(error) => Some(error),
+            Error::Xml(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<roxmltree::Error> for Error {
+    fn from(error: roxmltree::Error) -> Self {
+        Error::Xml(error)
+    }
+}
+
+/// Custom property value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Property {
+    /// Boolean value.
+    Bool(bool),
+    /// Integer value.
+    Int(i64),
+    /// Floating point value.
+    Float(f64),
+    /// String value, also used for colors and file paths.
+    String(String),
+    /// Reference to an object by its id.
+    Object(u32),
+    /// Nested set of properties.
+    Class(Properties),
+}
+
+/// Set of named custom properties.
+pub type Properties = HashMap<String, Property>;
+
+/// Set of tiles sharing the same image.
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    first_gid: u32,
+    name: String,
+    source: Option<String>,
+    image: Option<String>,
+    tile_size: Vector<i32>,
+    columns: u32,
+    count: u32,
+    properties: Properties,
+}
+
+impl Tileset {
+    /// Get global id of the first tile in this tileset.
+    pub fn first_gid(&self) -> u32 {
+        self.first_gid
+    }
+
+    /// Get tileset name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get path to the external tileset file, if any.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Get path to the tileset image, if any.
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
+    /// Get size of a single tile in pixels.
+    pub fn tile_size(&self) -> Vector<i32> {
+        self.tile_size
+    }
+
+    /// Get count of tile columns in the tileset image.
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    /// Get count of tiles in this tileset.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Get custom properties.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// Layer of tile global ids.
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+    name: String,
+    width: usize,
+    height: usize,
+    gids: Vec<u32>,
+    visible: bool,
+    offset: Vector<f32>,
+    properties: Properties,
+}
+
+impl TileLayer {
+    /// Get layer name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get width in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get height in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get raw global ids with flip flags, row by row.
+    pub fn gids(&self) -> &[u32] {
+        &self.gids
+    }
+
+    /// Check if the layer is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Get layer offset in pixels.
+    pub fn offset(&self) -> Vector<f32> {
+        self.offset
+    }
+
+    /// Get custom properties.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// Shape of the map object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// Rectangle of given size.
+    Rectangle(Vector<f32>),
+    /// Ellipse fitting rectangle of given size.
+    Ellipse(Vector<f32>),
+    /// Single point.
+    Point,
+    /// Closed polygon with points relative to the object position.
+    Polygon(Vec<Vector<f32>>),
+    /// Open polyline with points relative to the object position.
+    Polyline(Vec<Vector<f32>>),
+    /// Tile with given raw global id, scaled to given size.
+    Tile(u32, Vector<f32>),
+}
+
+/// Object placed on the map.
+#[derive(Clone, Debug)]
+pub struct Object {
+    id: u32,
+    name: String,
+    class: String,
+    position: Vector<f32>,
+    rotation: f32,
+    visible: bool,
+    shape: Shape,
+    properties: Properties,
+}
+
+impl Object {
+    /// Get unique object id.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Get object name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get object class, also known as type.
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    /// Get object position in pixels.
+    pub fn position(&self) -> Vector<f32> {
+        self.position
+    }
+
+    /// Get clockwise rotation in degrees.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Check if the object is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Get object shape.
+    pub fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    /// Get custom properties.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// Layer of objects.
+#[derive(Clone, Debug)]
+pub struct ObjectLayer {
+    name: String,
+    objects: Vec<Object>,
+    visible: bool,
+    offset: Vector<f32>,
+    properties: Properties,
+}
+
+impl ObjectLayer {
+    /// Get layer name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get objects of this layer.
+    pub fn objects(&self) -> &[Object] {
+        &self.objects
+    }
+
+    /// Check if the layer is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Get layer offset in pixels.
+    pub fn offset(&self) -> Vector<f32> {
+        self.offset
+    }
+
+    /// Get custom properties.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+}
+
+/// Map layer.
+/// Group layers are flattened with their offsets and visibility applied to children.
+#[derive(Clone, Debug)]
+pub enum Layer {
+    /// Layer of tiles.
+    Tiles(TileLayer),
+    /// Layer of objects.
+    Objects(ObjectLayer),
+}
+
+/// Orthogonal Tiled map.
+#[derive(Clone, Debug)]
+pub struct Map {
+    width: usize,
+    height: usize,
+    tile_size: Vector<i32>,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+    properties: Properties,
+}
+
+#[derive(Clone, Copy)]
+struct Group {
+    visible: bool,
+    offset: Vector<f32>,
+}
+
+impl Group {
+    fn root() -> Self {
+        Self {
+            visible: true,
+            offset: Vector::new(0.0, 0.0),
+        }
+    }
+
+    fn nest(self, visible: bool, offset: Vector<f32>) -> Self {
+        Self {
+            visible: self.visible && visible,
+            offset: self.offset + offset,
+        }
+    }
+}
+
+impl Map {
+    /// Load map from `.tmx` or `.tmj` file.
+    /// JSON format is expected for `.tmj` and `.json` extensions, XML format otherwise.
+    /// External tilesets are loaded relative to the map file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let json = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("tmj" | "json")
+        );
+        let mut map = if json {
+            Self::from_json(&text)?
+        } else {
+            Self::from_tmx(&text)?
+        };
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        for tileset in map.tilesets.iter_mut() {
+            let Some(source) = tileset.source.clone() else {
+                continue;
+            };
+            let text = std::fs::read_to_string(directory.join(&source))?;
+            let mut loaded = if source.ends_with(".tsj") || source.ends_with(".json") {
+                json_tileset(&serde_json::from_str(&text)?, tileset.first_gid)
+            } else {
+                let document = roxmltree::Document::parse(&text)?;
+                xml_tileset(document.root_element(), tileset.first_gid)
+            };
+            loaded.source = Some(source);
+            *tileset = loaded;
+        }
+        Ok(map)
+    }
+
+    /// Parse map from JSON text.
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(text)?;
+        if json_bool(&value, "infinite", false) {
+            return Err(Error::Unsupported("infinite map"));
+        }
+        let mut layers = Vec::new();
+        json_layers(&value, Group::root(), &mut layers)?;
+        let tilesets = value["tilesets"]
+            .as_array()
+            .map(|tilesets| {
+                tilesets
+                    .iter()
+                    .map(|tileset| json_tileset(tileset, json_u32(tileset, "firstgid")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            width: json_u32(&value, "width") as usize,
+            height: json_u32(&value, "height") as usize,
+            tile_size: Vector::new(
+                json_u32(&value, "tilewidth") as i32,
+                json_u32(&value, "tileheight") as i32,
+            ),
+            tilesets,
+            layers,
+            properties: json_properties(&value),
+        }
+        .validated()
+    }
+
+    /// Parse map from TMX text.
+    pub fn from_tmx(text: &str) -> Result<Self, Error> {
+        let document = roxmltree::Document::parse(text)?;
+        let root = document.root_element();
+        if !root.has_tag_name("map") {
+            return Err(Error::Malformed("map element"));
+        }
+        if xml_attribute(root, "infinite").unwrap_or(0) != 0 {
+            return Err(Error::Unsupported("infinite map"));
+        }
+        let mut layers = Vec::new();
+        xml_layers(root, Group::root(), &mut layers)?;
+        let tilesets = root
+            .children()
+            .filter(|node| node.has_tag_name("tileset"))
+            .map(|node| xml_tileset(node, xml_attribute(node, "firstgid").unwrap_or(1)))
+            .collect();
+        Self {
+            width: xml_attribute(root, "width").unwrap_or(0),
+            height: xml_attribute(root, "height").unwrap_or(0),
+            tile_size: Vector::new(
+                xml_attribute(root, "tilewidth").unwrap_or(0),
+                xml_attribute(root, "tileheight").unwrap_or(0),
+            ),
+            tilesets,
+            layers,
+            properties: xml_properties(root),
+        }
+        .validated()
+    }
+
+    // Tile layers are expected to cover the whole map.
+    fn validated(mut self) -> Result<Self, Error> {
+        let count = tile_count(self.width, self.height)?;
+        for layer in self.layers.iter() {
+            if let Layer::Tiles(tiles) = layer {
+                let size = (tiles.width, tiles.height);
+                if size != (self.width, self.height) || tiles.gids.len() != count {
+                    return Err(Error::Malformed("tile layer size"));
+                }
+            }
+        }
+        self.tilesets.sort_by_key(Tileset::first_gid);
+        Ok(self)
+    }
+
+    /// Get width in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get height in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get size of a single tile in pixels.
+    pub fn tile_size(&self) -> Vector<i32> {
+        self.tile_size
+    }
+
+    /// Get tilesets ordered by their first global id.
+    pub fn tilesets(&self) -> &[Tileset] {
+        &self.tilesets
+    }
+
+    /// Get layers from bottom to top.
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Get custom properties.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Get first object layer with given name.
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Objects(objects) if objects.name == name => Some(objects),
+            _ => None,
+        })
+    }
+
+    /// Convert raw global id with flip flags into the index of its tileset and a tile.
+    /// Tile index is local to the tileset.
+    pub fn tile(&self, gid: u32) -> Option<(usize, Tile)> {
+        let id = gid & GID_MASK;
+        let tileset = self
+            .tilesets
+            .iter()
+            .rposition(|tileset| tileset.first_gid != 0 && tileset.first_gid <= id)?;
+        let index = (id - self.tilesets[tileset].first_gid) as usize;
+        let horizontal = gid & FLIPPED_HORIZONTALLY != 0;
+        let vertical = gid & FLIPPED_VERTICALLY != 0;
+        let diagonal = gid & FLIPPED_DIAGONALLY != 0;
+        let (flip, rotation) = match (diagonal, horizontal, vertical) {
+            (false, false, false) => (Flip::None, Rotation::None),
+            (false, true, false) => (Flip::Horizontal, Rotation::None),
+            (false, false, true) => (Flip::Vertical, Rotation::None),
+            (false, true, true) => (Flip::Both, Rotation::None),
+            (true, false, false) => (Flip::Vertical, Rotation::Clockwise90),
+            (true, true, false) => (Flip::None, Rotation::Clockwise90),
+            (true, false, true) => (Flip::None, Rotation::Clockwise270),
+            (true, true, true) => (Flip::Horizontal, Rotation::Clockwise90),
+        };
+        let tile = Tile::new(index).with_flip(flip).with_rotation(rotation);
+        Some((tileset, tile))
+    }
+
+    /// Build tilemap of tiles from the tileset by its `index` for all tile layers,
+    /// preserving their visibility, tiles of other tilesets are left empty.
+    /// To keep layer order with multiple tilesets draw same layer of each tilemap before the next one.
+    /// Layer offsets are not applied.
+    /// Returns `None` if there is no such tileset.
+    pub fn tilemap(&self, index: usize) -> Option<Tilemap> {
+        let tile = self.tilesets.get(index)?.tile_size;
+        let max_image_size = Vector::new(
+            self.tile_size.x().max(tile.x()),
+            self.tile_size.y().max(tile.y()),
+        );
+        let mut tilemap = Tilemap::new(self.width, self.height, self.tile_size)
+            .with_max_image_size(max_image_size);
+        for layer in self.layers.iter() {
+            let Layer::Tiles(tiles) = layer else {
+                continue;
+            };
+            let target = tilemap.add_layer();
+            target.set_visible(tiles.visible);
+            for (position, gid) in tiles.gids.iter().enumerate() {
+                let x = (position % self.width) as i32;
+                let y = (position / self.width) as i32;
+                let tile = self
+                    .tile(*gid)
+                    .filter(|(tileset, _)| *tileset == index)
+                    .map(|(_, tile)| tile);
+                target.set_tile(x, y, tile);
+            }
+        }
+        Some(tilemap)
+    }
+}
+
+fn tile_count(width: usize, height: usize) -> Result<usize, Error> {
+    width
+        .checked_mul(height)
+        .filter(|count| *count <= MAX_TILES)
+        .ok_or(Error::TooLarge("map size"))
+}
+
+// Decompressed data is limited to `count` tiles.
+fn decode(
+    text: &str,
+    encoding: Option<&str>,
+    compression: Option<&str>,
+    count: usize,
+) -> Result<Vec<u32>, Error> {
+    let limit = count * 4;
+    match encoding {
+        Some("csv") => text
+            .split(',')
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::Malformed("tile id"))
+            })
+            .collect(),
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map_err(|_| Error::Malformed("base64 tile data"))?;
+            let bytes = match compression {
+                None | Some("") => bytes,
+                Some("zlib") => {
+                    miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(&bytes, limit)
+                        .map_err(|_| Error::Malformed("zlib tile data"))?
+                }
+                Some("gzip") => inflate_gzip(&bytes, limit)?,
+                Some(_) => return Err(Error::Unsupported("tile data compression")),
+            };
+            if bytes.len() % 4 != 0 {
+                return Err(Error::Malformed("tile data length"));
+            }
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        _ => Err(Error::Unsupported("tile data encoding")),
+    }
+}
+
+fn inflate_gzip(bytes: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    const EXTRA: u8 = 4;
+    const NAME: u8 = 8;
+    const COMMENT: u8 = 16;
+    const CRC: u8 = 2;
+    let malformed = Error::Malformed("gzip tile data");
+
+    if bytes.len() < 18 || bytes[0..3] != [0x1f, 0x8b, 8] {
+        return Err(malformed);
+    }
+    let flags = bytes[3];
+    let mut position = 10;
+    if flags & EXTRA != 0 {
+        let length = u16::from_le_bytes([bytes[position], bytes[position + 1]]) as usize;
+        position += 2 + length;
+    }
+    for flag in [NAME, COMMENT] {
+        if flags & flag != 0 {
+            let end = bytes
+                .get(position..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0));
+            position += end.ok_or(Error::Malformed("gzip tile data"))? + 1;
+        }
+    }
+    if flags & CRC != 0 {
+        position += 2;
+    }
+    let data = bytes.get(position..).ok_or(malformed)?;
+    miniz_oxide::inflate::decompress_to_vec_with_limit(data, limit)
+        .map_err(|_| Error::Malformed("gzip tile data"))
+}
+
+fn json_u32(value: &Value, key: &str) -> u32 {
+    value[key].as_u64().unwrap_or(0) as u32
+}
+
+fn json_f32(value: &Value, key: &str) -> f32 {
+    value[key].as_f64().unwrap_or(0.0) as f32
+}
+
+fn json_bool(value: &Value, key: &str, default: bool) -> bool {
+    value[key].as_bool().unwrap_or(default)
+}
+
+fn json_string(value: &Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_owned()
+}
+
+fn json_property(kind: &str, value: &Value) -> Property {
+    match kind {
+        "bool" => Property::Bool(value.as_bool().unwrap_or_default()),
+        "int" => Property::Int(value.as_i64().unwrap_or_default()),
+        "float" => Property::Float(value.as_f64().unwrap_or_default()),
+        "object" => Property::Object(value.as_u64().unwrap_or_default() as u32),
+        "class" => Property::Class(
+            value
+                .as_object()
+                .map(|members| {
+                    members
+                        .iter()
+                        .map(|(name, member)| {
+                            let kind = match member {
+                                Value::Bool(_) => "bool",
+                                Value::Number(number) if number.is_i64() => "int",
+                                Value::Number(_) => "float",
+                                Value::Object(_) => "class",
+                                _ => "string",
+                            };
+                            (name.clone(), json_property(kind, member))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        _ => Property::String(value.as_str().unwrap_or_default().to_owned()),
+    }
+}
+
+fn json_properties(value: &Value) -> Properties {
+    value["properties"]
+        .as_array()
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|property| {
+                    let kind = property["type"].as_str().unwrap_or("string");
+                    let value = json_property(kind, &property["value"]);
+                    (json_string(property, "name"), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn json_tileset(value: &Value, first_gid: u32) -> Tileset {
+    Tileset {
+        first_gid,
+        name: json_string(value, "name"),
+        source: value["source"].as_str().map(str::to_owned),
+        image: value["image"].as_str().map(str::to_owned),
+        tile_size: Vector::new(
+            json_u32(value, "tilewidth") as i32,
+            json_u32(value, "tileheight") as i32,
+        ),
+        columns: json_u32(value, "columns"),
+        count: json_u32(value, "tilecount"),
+        properties: json_properties(value),
+    }
+}
+
+fn json_points(value: &Value) -> Vec<Vector<f32>> {
+    value
+        .as_array()
+        .map(|points| {
+            points
+                .iter()
+                .map(|point| Vector::new(json_f32(point, "x"), json_f32(point, "y")))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn json_object(value: &Value) -> Object {
+    let size = Vector::new(json_f32(value, "width"), json_f32(value, "height"));
+    let shape = if json_bool(value, "point", false) {
+        Shape::Point
+    } else if json_bool(value, "ellipse", false) {
+        Shape::Ellipse(size)
+    } else if value["polygon"].is_array() {
+        Shape::Polygon(json_points(&value["polygon"]))
+    } else if value["polyline"].is_array() {
+        Shape::Polyline(json_points(&value["polyline"]))
+    } else if let Some(gid) = value["gid"].as_u64() {
+        Shape::Tile(gid as u32, size)
+    } else {
+        Shape::Rectangle(size)
+    };
+    let class = value["class"]
+        .as_str()
+        .or_else(|| value["type"].as_str())
+        .unwrap_or_default()
+        .to_owned();
+    Object {
+        id: json_u32(value, "id"),
+        name: json_string(value, "name"),
+        class,
+        position: Vector::new(json_f32(value, "x"), json_f32(value, "y")),
+        rotation: json_f32(value, "rotation"),
+        visible: json_bool(value, "visible", true),
+        shape,
+        properties: json_properties(value),
+    }
+}
+
+fn json_layers(value: &Value, group: Group, layers: &mut Vec<Layer>) -> Result<(), Error> {
+    let Some(children) = value["layers"].as_array() else {
+        return Ok(());
+    };
+    for layer in children {
+        let offset = Vector::new(json_f32(layer, "offsetx"), json_f32(layer, "offsety"));
+        let nested = group.nest(json_bool(layer, "visible", true), offset);
+        match layer["type"].as_str() {
+            Some("tilelayer") => {
+                let width = json_u32(layer, "width") as usize;
+                let height = json_u32(layer, "height") as usize;
+                let count = tile_count(width, height)?;
+                let gids = match &layer["data"] {
+                    Value::Array(gids) => gids
+                        .iter()
+                        .map(|gid| gid.as_u64().unwrap_or(0) as u32)
+                        .collect(),
+                    Value::String(data) => decode(
+                        data,
+                        layer["encoding"].as_str(),
+                        layer["compression"].as_str(),
+                        count,
+                    )?,
+                    _ => return Err(Error::Malformed("tile layer data")),
+                };
+                layers.push(Layer::Tiles(TileLayer {
+                    name: json_string(layer, "name"),
+                    width,
+                    height,
+                    gids,
+                    visible: nested.visible,
+                    offset: nested.offset,
+                    properties: json_properties(layer),
+                }));
+            }
+            Some("objectgroup") => {
+                let objects = layer["objects"]
+                    .as_array()
+                    .map(|objects| objects.iter().map(json_object).collect())
+                    .unwrap_or_default();
+                layers.push(Layer::Objects(ObjectLayer {
+                    name: json_string(layer, "name"),
+                    objects,
+                    visible: nested.visible,
+                    offset: nested.offset,
+                    properties: json_properties(layer),
+                }));
+            }
+            Some("group") => json_layers(layer, nested, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn xml_attribute<T: std::str::FromStr>(node: Node, name: &str) -> Option<T> {
+    node.attribute(name)?.parse().ok()
+}
+
+fn xml_child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn xml_properties(node: Node) -> Properties {
+    let Some(properties) = xml_child(node, "properties") else {
+        return Properties::new();
+    };
+    properties
+        .children()
+        .filter(|child| child.has_tag_name("property"))
+        .map(|property| {
+            let name = property.attribute("name").unwrap_or_default().to_owned();
+            let text = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or_default();
+            let value = match property.attribute("type").unwrap_or("string") {
+                "bool" => Property::Bool(text == "true"),
+                "int" => Property::Int(text.parse().unwrap_or_default()),
+                "float" => Property::Float(text.parse().unwrap_or_default()),
+                "object" => Property::Object(text.parse().unwrap_or_default()),
+                "class" => Property::Class(xml_properties(property)),
+                _ => Property::String(text.to_owned()),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn xml_tileset(node: Node, first_gid: u32) -> Tileset {
+    Tileset {
+        first_gid,
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        source: node.attribute("source").map(str::to_owned),
+        image: xml_child(node, "image")
+            .and_then(|image| image.attribute("source"))
+            .map(str::to_owned),
+        tile_size: Vector::new(
+            xml_attribute(node, "tilewidth").unwrap_or(0),
+            xml_attribute(node, "tileheight").unwrap_or(0),
+        ),
+        columns: xml_attribute(node, "columns").unwrap_or(0),
+        count: xml_attribute(node, "tilecount").unwrap_or(0),
+        properties: xml_properties(node),
+    }
+}
+
+fn xml_points(node: Node) -> Vec<Vector<f32>> {
+    node.attribute("points")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|point| {
+            let (x, y) = point.split_once(',')?;
+            Some(Vector::new(x.parse().ok()?, y.parse().ok()?))
+        })
+        .collect()
+}
+
+fn xml_object(node: Node) -> Object {
+    let size = Vector::new(
+        xml_attribute(node, "width").unwrap_or(0.0),
+        xml_attribute(node, "height").unwrap_or(0.0),
+    );
+    let shape = if xml_child(node, "point").is_some() {
+        Shape::Point
+    } else if xml_child(node, "ellipse").is_some() {
+        Shape::Ellipse(size)
+    } else if let Some(polygon) = xml_child(node, "polygon") {
+        Shape::Polygon(xml_points(polygon))
+    } else if let Some(polyline) = xml_child(node, "polyline") {
+        Shape::Polyline(xml_points(polyline))
+    } else if let Some(gid) = xml_attribute(node, "gid") {
+        Shape::Tile(gid, size)
+    } else {
+        Shape::Rectangle(size)
+    };
+    let class = node
+        .attribute("class")
+        .or_else(|| node.attribute("type"))
+        .unwrap_or_default()
+        .to_owned();
+    Object {
+        id: xml_attribute(node, "id").unwrap_or(0),
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        class,
+        position: Vector::new(
+            xml_attribute(node, "x").unwrap_or(0.0),
+            xml_attribute(node, "y").unwrap_or(0.0),
+        ),
+        rotation: xml_attribute(node, "rotation").unwrap_or(0.0),
+        visible: xml_attribute(node, "visible").unwrap_or(1) != 0,
+        shape,
+        properties: xml_properties(node),
+    }
+}
+
+fn xml_layers(node: Node, group: Group, layers: &mut Vec<Layer>) -> Result<(), Error> {
+    for layer in node.children().filter(Node::is_element) {
+        let offset = Vector::new(
+            xml_attribute(layer, "offsetx").unwrap_or(0.0),
+            xml_attribute(layer, "offsety").unwrap_or(0.0),
+        );
+        let visible = xml_attribute(layer, "visible").unwrap_or(1) != 0;
+        let nested = group.nest(visible, offset);
+        let name = layer.attribute("name").unwrap_or_default().to_owned();
+        match layer.tag_name().name() {
+            "layer" => {
+                let width = xml_attribute(layer, "width").unwrap_or(0);
+                let height = xml_attribute(layer, "height").unwrap_or(0);
+                let count = tile_count(width, height)?;
+                let data = xml_child(layer, "data").ok_or(Error::Malformed("tile layer data"))?;
+                let gids = match data.attribute("encoding") {
+                    None => data
+                        .children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| xml_attribute(tile, "gid").unwrap_or(0))
+                        .collect(),
+                    encoding => decode(
+                        data.text().unwrap_or_default(),
+                        encoding,
+                        data.attribute("compression"),
+                        count,
+                    )?,
+                };
+                layers.push(Layer::Tiles(TileLayer {
+                    name,
+                    width,
+                    height,
+                    gids,
+                    visible: nested.visible,
+                    offset: nested.offset,
+                    properties: xml_properties(layer),
+                }));
+            }
+            "objectgroup" => {
+                let objects = layer
+                    .children()
+                    .filter(|child| child.has_tag_name("object"))
+                    .map(xml_object)
+                    .collect();
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    objects,
+                    visible: nested.visible,
+                    offset: nested.offset,
+                    properties: xml_properties(layer),
+                }));
+            }
+            "group" => xml_layers(layer, nested, layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIDS: [u32; 6] = [1, 2, 0, FLIPPED_HORIZONTALLY | 3, 4, 0];
+
+    fn gid_bytes() -> Vec<u8> {
+        GIDS.iter().flat_map(|gid| gid.to_le_bytes()).collect()
+    }
+
+    fn base64(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    fn tmx(data: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<map width="3" height="2" tilewidth="8" tileheight="8" infinite="0">
+ <tileset firstgid="1" name="tiles" tilewidth="8" tileheight="8" tilecount="4" columns="2"/>
+ <layer name="ground" width="3" height="2">
+  {}
+ </layer>
+</map>"#,
+            data
+        )
+    }
+
+    fn gids(map: &Map) -> &[u32] {
+        match &map.layers()[0] {
+            Layer::Tiles(layer) => layer.gids(),
+            _ => panic!("tile layer expected"),
+        }
+    }
+
+    #[test]
+    fn decodes_csv() {
+        let csv = GIDS.map(|gid| gid.to_string()).join(",\n");
+        let map = Map::from_tmx(&tmx(&format!(r#"<data encoding="csv">{}</data>"#, csv))).unwrap();
+        assert_eq!(gids(&map), GIDS);
+        assert_eq!((map.width(), map.height()), (3, 2));
+        assert_eq!(map.tile_size(), Vector::new(8, 8));
+        assert_eq!(map.tilesets()[0].name(), "tiles");
+    }
+
+    #[test]
+    fn decodes_base64() {
+        let data = format!(r#"<data encoding="base64">{}</data>"#, base64(&gid_bytes()));
+        assert_eq!(gids(&Map::from_tmx(&tmx(&data)).unwrap()), GIDS);
+    }
+
+    #[test]
+    fn decodes_base64_zlib() {
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&gid_bytes(), 6);
+        let data = format!(
+            r#"<data encoding="base64" compression="zlib">{}</data>"#,
+            base64(&compressed)
+        );
+        assert_eq!(gids(&Map::from_tmx(&tmx(&data)).unwrap()), GIDS);
+    }
+
+    #[test]
+    fn decodes_base64_gzip() {
+        let mut gzip = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        gzip.extend_from_slice(&miniz_oxide::deflate::compress_to_vec(&gid_bytes(), 6));
+        gzip.extend_from_slice(&[0; 8]);
+        let data = format!(
+            r#"<data encoding="base64" compression="gzip">{}</data>"#,
+            base64(&gzip)
+        );
+        assert_eq!(gids(&Map::from_tmx(&tmx(&data)).unwrap()), GIDS);
+    }
+
+    #[test]
+    fn decodes_json_layers() {
+        let compressed = miniz_oxide::deflate::compress_to_vec_zlib(&gid_bytes(), 6);
+        let json = format!(
+            r#"{{"width": 3, "height": 2, "tilewidth": 8, "tileheight": 8, "layers": [
+                {{"type": "tilelayer", "name": "plain", "width": 3, "height": 2, "data": [1, 2, 0, 2147483651, 4, 0]}},
+                {{"type": "tilelayer", "name": "packed", "width": 3, "height": 2,
+                  "encoding": "base64", "compression": "zlib", "data": "{}"}}
+            ]}}"#,
+            base64(&compressed)
+        );
+        let map = Map::from_json(&json).unwrap();
+        for layer in map.layers() {
+            let Layer::Tiles(layer) = layer else {
+                panic!("tile layer expected");
+            };
+            assert_eq!(layer.gids(), GIDS);
+        }
+    }
+
+    fn transform(grid: [[u8; 3]; 3], map: impl Fn(usize, usize) -> (usize, usize)) -> [[u8; 3]; 3] {
+        let mut result = [[0; 3]; 3];
+        for (y, row) in result.iter_mut().enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let (source_x, source_y) = map(x, y);
+                *value = grid[source_y][source_x];
+            }
+        }
+        result
+    }
+
+    fn apply_tile(grid: [[u8; 3]; 3], tile: Tile) -> [[u8; 3]; 3] {
+        let grid = match tile.flip() {
+            Flip::None => grid,
+            Flip::Horizontal => transform(grid, |x, y| (2 - x, y)),
+            Flip::Vertical => transform(grid, |x, y| (x, 2 - y)),
+            Flip::Both => transform(grid, |x, y| (2 - x, 2 - y)),
+        };
+        match tile.rotation() {
+            Rotation::None => grid,
+            Rotation::Clockwise90 => transform(grid, |x, y| (y, 2 - x)),
+            Rotation::Clockwise180 => transform(grid, |x, y| (2 - x, 2 - y)),
+            Rotation::Clockwise270 => transform(grid, |x, y| (2 - y, x)),
+        }
+    }
+
+    #[test]
+    fn decodes_flip_flags() {
+        let map = Map::from_tmx(&tmx(r#"<data encoding="csv">0,0,0,0,0,0</data>"#)).unwrap();
+        let grid = [[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+        for flags in 0..8_u32 {
+            let gid = (flags << 29) | 5;
+            let (tileset, tile) = map.tile(gid).unwrap();
+            assert_eq!((tileset, tile.index()), (0, 4));
+
+            // Tiled applies diagonal flip first, then horizontal, then vertical.
+            let mut expected = grid;
+            if flags & 1 != 0 {
+                expected = transform(expected, |x, y| (y, x));
+            }
+            if flags & 4 != 0 {
+                expected = transform(expected, |x, y| (2 - x, y));
+            }
+            if flags & 2 != 0 {
+                expected = transform(expected, |x, y| (x, 2 - y));
+            }
+            assert_eq!(apply_tile(grid, tile), expected, "flags {:03b}", flags);
+        }
+        assert_eq!(map.tile(0), None);
+        assert_eq!(map.tile(FLIPPED_HORIZONTALLY), None);
+    }
+
+    #[test]
+    fn splits_tiles_by_tileset() {
+        let json = r#"{"width": 3, "height": 1, "tilewidth": 8, "tileheight": 8,
+            "tilesets": [
+                {"firstgid": 10, "name": "large", "tilewidth": 16, "tileheight": 16},
+                {"firstgid": 1, "name": "small", "tilewidth": 8, "tileheight": 8}
+            ],
+            "layers": [{"type": "tilelayer", "width": 3, "height": 1, "data": [1, 10, 12]}]}"#;
+        let map = Map::from_json(json).unwrap();
+        assert_eq!(map.tilesets()[0].name(), "small");
+        assert_eq!(
+            map.tile(9).map(|(tileset, tile)| (tileset, tile.index())),
+            Some((0, 8))
+        );
+        assert_eq!(
+            map.tile(12).map(|(tileset, tile)| (tileset, tile.index())),
+            Some((1, 2))
+        );
+
+        let small = map.tilemap(0).unwrap();
+        let large = map.tilemap(1).unwrap();
+        let layer = |tilemap: &Tilemap| -> Vec<Option<usize>> {
+            let layer = tilemap.layer(0).unwrap();
+            (0..3)
+                .map(|x| layer.tile(x, 0).map(|tile| tile.index()))
+                .collect()
+        };
+        assert_eq!(layer(&small), [Some(0), None, None]);
+        assert_eq!(layer(&large), [None, Some(0), Some(2)]);
+        assert_eq!(large.max_image_size(), Vector::new(16, 16));
+        assert!(map.tilemap(2).is_none());
+    }
+
+    #[test]
+    fn malformed_data_is_error() {
+        let cases = [
+            r#"<data encoding="csv">1,two,3,4,5,6</data>"#.to_owned(),
+            r#"<data encoding="base64">not base64!</data>"#.to_owned(),
+            format!(r#"<data encoding="base64">{}</data>"#, base64(&[1, 0, 0])),
+            format!(
+                r#"<data encoding="base64" compression="zlib">{}</data>"#,
+                base64(&gid_bytes())
+            ),
+            format!(
+                r#"<data encoding="base64" compression="gzip">{}</data>"#,
+                base64(&[0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0, 0, 0, 0, 0, 0])
+            ),
+            format!(
+                r#"<data encoding="base64" compression="zstd">{}</data>"#,
+                base64(&gid_bytes())
+            ),
+            r#"<data encoding="hex">00</data>"#.to_owned(),
+        ];
+        for data in cases {
+            assert!(Map::from_tmx(&tmx(&data)).is_err(), "{}", data);
+        }
+        assert!(matches!(
+            Map::from_tmx("<tileset/>"),
+            Err(Error::Malformed(_))
+        ));
+        assert!(matches!(Map::from_tmx("<map"), Err(Error::Xml(_))));
+        assert!(matches!(
+            Map::from_tmx(r#"<map infinite="1"/>"#),
+            Err(Error::Unsupported(_))
+        ));
+        assert!(matches!(Map::from_json("{"), Err(Error::Json(_))));
+        let short = Map::from_tmx(&tmx(r#"<data encoding="csv">1,2,3</data>"#));
+        assert!(matches!(short, Err(Error::Malformed(_))));
+        assert!(matches!(
+            Map::from_json(r#"{"layers": [{"type": "tilelayer", "data": 5}]}"#),
+            Err(Error::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_document_is_error() {
+        let data = tmx(r#"<data encoding="csv">1,2,0,3,4,0</data>"#);
+        for length in 0..data.len() {
+            assert!(Map::from_tmx(&data[..length]).is_err());
+        }
+        let json = r#"{"width": 1, "height": 1, "layers": [{"type": "tilelayer", "width": 1, "height": 1, "data": [1]}]}"#;
+        for length in 0..json.len() {
+            assert!(Map::from_json(&json[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn oversized_map_is_error() {
+        let huge = format!(r#"{{"width": {0}, "height": {0}, "layers": []}}"#, u32::MAX);
+        assert!(matches!(Map::from_json(&huge), Err(Error::TooLarge(_))));
+        let layer = format!(
+            r#"<map width="1" height="1"><layer width="{0}" height="{0}"><data encoding="csv">1</data></layer></map>"#,
+            u32::MAX
+        );
+        assert!(matches!(Map::from_tmx(&layer), Err(Error::TooLarge(_))));
+
+        // Decompressed data larger than the layer is rejected without being inflated in full.
+        let bomb = miniz_oxide::deflate::compress_to_vec_zlib(&[0; 1 << 20], 6);
+        let data = format!(
+            r#"<data encoding="base64" compression="zlib">{}</data>"#,
+            base64(&bomb)
+        );
+        assert!(matches!(
+            Map::from_tmx(&tmx(&data)),
+            Err(Error::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Blit, Flip, Image, ImageMut, Painter, Rotation};
+use crate::util::getter::Getter;
+use crate::util::vector::Vector;
+
+/// Reference to a tileset image with its orientation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tile {
+    index: usize,
+    flip: Flip,
+    rotation: Rotation,
+}
+
+impl Tile {
+    /// Create new tile referencing tileset image by `index`.
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            flip: Flip::None,
+            rotation: Rotation::None,
+        }
+    }
+
+    /// Get new tile with desired flip.
+    pub fn with_flip(self, flip: Flip) -> Self {
+        Self { flip, ..self }
+    }
+
+    /// Get new tile with desired clockwise rotation.
+    /// Rotation is applied after flip.
+    pub fn with_rotation(self, rotation: Rotation) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// Get tileset image index.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get tile flip.
+    pub fn flip(&self) -> Flip {
+        self.flip
+    }
+
+    /// Get tile rotation.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+}
+
+/// Single grid of tiles in the tilemap.
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+    tiles: Box<[Option<Tile>]>,
+    width: usize,
+    height: usize,
+    visible: bool,
+}
+
+impl TileLayer {
+    fn new(width: usize, height: usize) -> Self {
+        let tiles = vec![None; width * height].into_boxed_slice();
+        Self {
+            tiles,
+            width,
+            height,
+            visible: true,
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(x as usize + y as usize * self.width)
+        }
+    }
+
+    /// Get tile at grid position.
+    pub fn tile(&self, x: i32, y: i32) -> Option<Tile> {
+        self.tiles[self.index(x, y)?]
+    }
+
+    /// Set tile at grid position.
+    /// Returns `false` if the position is out of the grid.
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: Option<Tile>) -> bool {
+        let Some(index) = self.index(x, y) else {
+            return false;
+        };
+        self.tiles[index] = tile;
+        true
+    }
+
+    /// Fill the whole layer with the `tile`.
+    pub fn fill(&mut self, tile: Option<Tile>) {
+        self.tiles.fill(tile);
+    }
+
+    /// Check if the layer is drawn.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Set layer visibility.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+}
+
+/// Layered grid of tiles referencing images in a tileset.
+#[derive(Clone, Debug)]
+pub struct Tilemap {
+    width: usize,
+    height: usize,
+    tile_size: Vector<i32>,
+    max_image_size: Vector<i32>,
+    layers: Vec<TileLayer>,
+}
+
+impl Tilemap {
+    /// Create new tilemap of `width` by `height` tiles, each of `tile_size` pixels, without layers.
+    pub fn new<S: Into<Vector<i32>>>(width: usize, height: usize, tile_size: S) -> Self {
+        let tile_size = tile_size.into();
+        Self {
+            width,
+            height,
+            tile_size,
+            max_image_size: tile_size,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Get new tilemap expecting tileset images up to `max_image_size` pixels.
+    /// Images larger than a tile overhang it to the right and down,
+    /// so this size is used to find tiles reaching into the view.
+    pub fn with_max_image_size<S: Into<Vector<i32>>>(self, max_image_size: S) -> Self {
+        let max_image_size = max_image_size.into();
+        Self {
+            max_image_size,
+            ..self
+        }
+    }
+
+    /// Get width in tiles.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get height in tiles.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get size of a single tile in pixels.
+    pub fn tile_size(&self) -> Vector<i32> {
+        self.tile_size
+    }
+
+    /// Get maximal expected size of tileset images in pixels.
+    pub fn max_image_size(&self) -> Vector<i32> {
+        self.max_image_size
+    }
+
+    /// Add new empty layer on top of others and get mutable reference to it.
+    pub fn add_layer(&mut self) -> &mut TileLayer {
+        self.layers.push(TileLayer::new(self.width, self.height));
+        self.layers.last_mut().unwrap()
+    }
+
+    /// Get layers from bottom to top.
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers
+    }
+
+    /// Get layer by index.
+    pub fn layer(&self, index: usize) -> Option<&TileLayer> {
+        self.layers.get(index)
+    }
+
+    /// Get mutable layer by index.
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut TileLayer> {
+        self.layers.get_mut(index)
+    }
+
+    /// Get grid position of the tile containing world `position`.
+    pub fn grid_position<I: Into<Vector<i32>>>(&self, position: I) -> Vector<i32> {
+        let position = position.into();
+        Vector::new(
+            position.x().div_euclid(self.tile_size.x()),
+            position.y().div_euclid(self.tile_size.y()),
+        )
+    }
+
+    /// Draw single layer with its top left corner at world origin as seen by `camera`.
+    /// Only tiles within painter clip rectangle are drawn.
+    /// Returns count of tiles drawn.
+    pub fn draw_layer<'b, T, C, F, O, U, P>(
+        &self,
+        painter: &mut Painter<T>,
+        layer: usize,
+        camera: C,
+        tileset: &'b dyn Getter<Index = usize, Item = U>,
+        function: F,
+    ) -> usize
+    where
+        T: ImageMut<Pixel = P>,
+        P: Clone,
+        C: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O>,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        U::PixelRef<'b>: Deref<Target = O>,
+        for<'c> T::PixelRef<'c>: Deref<Target = P>,
+        for<'c> T::PixelMut<'c>: DerefMut<Target = P>,
+    {
+        let Some(layer) = self.layers.get(layer) else {
+            return 0;
+        };
+        if self.tile_size.x() <= 0 || self.tile_size.y() <= 0 {
+            return 0;
+        }
+
+        let camera = camera.into();
+        let (clip_from, clip_to) = painter.clip();
+        let view_start = camera - painter.offset() + clip_from;
+        let view_end = camera - painter.offset() + clip_to - (1, 1);
+        // Rotated images swap their sides, so the longest side may overhang in both directions.
+        let extent = self.max_image_size.x().max(self.max_image_size.y());
+        let overhang = Vector::new(
+            (extent - 1).div_euclid(self.tile_size.x()).max(0),
+            (extent - 1).div_euclid(self.tile_size.y()).max(0),
+        );
+        let start = self.grid_position(view_start) - overhang;
+        let end = self.grid_position(view_end);
+        let (start_x, start_y) = (start.x().max(0), start.y().max(0));
+        let end_x = end.x().min(self.width as i32 - 1);
+        let end_y = end.y().min(self.height as i32 - 1);
+
+        let mut function = function;
+        let mut drawn = 0;
+        for y in start_y..=end_y {
+            for x in start_x..=end_x {
+                let Some(tile) = layer.tile(x, y) else {
+                    continue;
+                };
+                let Some(image) = tileset.get(&tile.index) else {
+                    continue;
+                };
+                let at = Vector::new(x * self.tile_size.x(), y * self.tile_size.y()) - camera;
+                let blit = Blit::new()
+                    .with_flip(tile.flip)
+                    .with_rotation(tile.rotation);
+                painter.blit(at, image, blit, &mut function);
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+
+    /// Draw all visible layers from bottom to top as seen by `camera`.
+    /// Returns count of tiles drawn.
+    pub fn draw<'b, T, C, F, O, U, P>(
+        &self,
+        painter: &mut Painter<T>,
+        camera: C,
+        tileset: &'b dyn Getter<Index = usize, Item = U>,
+        function: F,
+    ) -> usize
+    where
+        T: ImageMut<Pixel = P>,
+        P: Clone,
+        C: Into<Vector<i32>>,
+        U: 'b + Image<Pixel = O>,
+        O: Clone,
+        F: FnMut(i32, i32, P, i32, i32, O) -> P,
+        U::PixelRef<'b>: Deref<Target = O>,
+        for<'c> T::PixelRef<'c>: Deref<Target = P>,
+        for<'c> T::PixelMut<'c>: DerefMut<Target = P>,
+    {
+        let camera = camera.into();
+        let mut function = function;
+        let mut drawn = 0;
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.visible {
+                drawn += self.draw_layer(painter, index, camera, tileset, &mut function);
+            }
+        }
+        drawn
+    }
+}
@@ -0,0 +1,208 @@
+use std::ops::DerefMut;
+
+use super::{Image, ImageMut};
+use crate::util::vector::Vector;
+
+fn region(
+    dimensions: Vector<i32>,
+    from: Vector<i32>,
+    to: Vector<i32>,
+) -> (Vector<i32>, Vector<i32>) {
+    let from = Vector::new(
+        from.x().clamp(0, dimensions.x()),
+        from.y().clamp(0, dimensions.y()),
+    );
+    let to = Vector::new(
+        to.x().clamp(from.x(), dimensions.x()),
+        to.y().clamp(from.y(), dimensions.y()),
+    );
+    (from, to - from)
+}
+
+/// Read-only rectangular region of another image.
+/// It implements `Image` only, so it can't be painted on.
+#[derive(Debug)]
+pub struct View<'a, I>
+where
+    I: ?Sized,
+{
+    image: &'a I,
+    origin: Vector<i32>,
+    size: Vector<i32>,
+}
+
+impl<'a, I> View<'a, I>
+where
+    I: Image + ?Sized,
+{
+    /// Create new view of the `image` region in `[from, to)` range.
+    /// The region is clamped by the image bounds.
+    pub fn new<P: Into<Vector<i32>>>(image: &'a I, from: P, to: P) -> Self {
+        let (origin, size) = region(image.dimensions(), from.into(), to.into());
+        Self {
+            image,
+            origin,
+            size,
+        }
+    }
+
+    /// Get position of this view in the underlying image.
+    pub fn origin(&self) -> Vector<i32> {
+        self.origin
+    }
+}
+
+impl<'a, I> Clone for View<'a, I>
+where
+    I: ?Sized,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, I> Copy for View<'a, I> where I: ?Sized {}
+
+impl<'a, I> Image for View<'a, I>
+where
+    I: Image + ?Sized,
+{
+    type Pixel = I::Pixel;
+    type PixelRef<'b>
+        = I::PixelRef<'b>
+    where
+        Self: 'b;
+
+    fn pixel(&self, position: Vector<i32>) -> Option<Self::PixelRef<'_>> {
+        if position.x() < 0
+            || position.y() < 0
+            || position.x() >= self.size.x()
+            || position.y() >= self.size.y()
+        {
+            None
+        } else {
+            // SAFETY: position is in bounds of the view, which is in bounds of the image.
+            Some(unsafe { self.image.unsafe_pixel(position + self.origin) })
+        }
+    }
+
+    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> Self::PixelRef<'_> {
+        self.image.unsafe_pixel(position + self.origin)
+    }
+
+    fn width(&self) -> i32 {
+        self.size.x()
+    }
+
+    fn height(&self) -> i32 {
+        self.size.y()
+    }
+}
+
+/// Mutable rectangular region of another image.
+#[derive(Debug)]
+pub struct ViewMut<'a, I>
+where
+    I: ?Sized,
+{
+    image: &'a mut I,
+    origin: Vector<i32>,
+    size: Vector<i32>,
+}
+
+impl<'a, I> ViewMut<'a, I>
+where
+    I: ImageMut + ?Sized,
+{
+    /// Create new mutable view of the `image` region in `[from, to)` range.
+    /// The region is clamped by the image bounds.
+    pub fn new<P: Into<Vector<i32>>>(image: &'a mut I, from: P, to: P) -> Self {
+        let (origin, size) = region(image.dimensions(), from.into(), to.into());
+        Self {
+            image,
+            origin,
+            size,
+        }
+    }
+
+    /// Get position of this view in the underlying image.
+    pub fn origin(&self) -> Vector<i32> {
+        self.origin
+    }
+}
+
+impl<'a, I> Image for ViewMut<'a, I>
+where
+    I: Image + ?Sized,
+{
+    type Pixel = I::Pixel;
+    type PixelRef<'b>
+        = I::PixelRef<'b>
+    where
+        Self: 'b;
+
+    fn pixel(&self, position: Vector<i32>) -> Option<Self::PixelRef<'_>> {
+        if position.x() < 0
+            || position.y() < 0
+            || position.x() >= self.size.x()
+            || position.y() >= self.size.y()
+        {
+            None
+        } else {
+            // SAFETY: position is in bounds of the view, which is in bounds of the image.
+            Some(unsafe { self.image.unsafe_pixel(position + self.origin) })
+        }
+    }
+
+    unsafe fn unsafe_pixel(&self, position: Vector<i32>) -> Self::PixelRef<'_> {
+        self.image.unsafe_pixel(position + self.origin)
+    }
+
+    fn width(&self) -> i32 {
+        self.size.x()
+    }
+
+    fn height(&self) -> i32 {
+        self.size.y()
+    }
+}
+
+impl<'a, I> ImageMut for ViewMut<'a, I>
+where
+    I: ImageMut + ?Sized,
+    I::Pixel: Clone,
+    for<'b> I::PixelMut<'b>: DerefMut<Target = I::Pixel>,
+{
+    type PixelMut<'b>
+        = I::PixelMut<'b>
+    where
+        Self: 'b;
+
+    fn pixel_mut(&mut self, position: Vector<i32>) -> Option<Self::PixelMut<'_>> {
+        if position.x() < 0
+            || position.y() < 0
+            || position.x() >= self.size.x()
+            || position.y() >= self.size.y()
+        {
+            None
+        } else {
+            // SAFETY: position is in bounds of the view, which is in bounds of the image.
+            Some(unsafe { self.image.unsafe_pixel_mut(position + self.origin) })
+        }
+    }
+
+    unsafe fn unsafe_pixel_mut(&mut self, position: Vector<i32>) -> Self::PixelMut<'_> {
+        self.image.unsafe_pixel_mut(position + self.origin)
+    }
+
+    fn clear(&mut self, color: Self::Pixel) {
+        for y in 0..self.size.y() {
+            for x in 0..self.size.x() {
+                // SAFETY: position is in bounds of the view, which is in bounds of the image.
+                unsafe {
+                    *self.image.unsafe_pixel_mut(self.origin + (x, y)) = color.clone();
+                }
+            }
+        }
+    }
+}